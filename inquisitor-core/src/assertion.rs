@@ -0,0 +1,688 @@
+use crate::error::InquisitorError;
+use reqwest::header::{HeaderMap, HeaderName};
+use std::time::Duration;
+
+/// Condition that a response must satisfy, written in a small expression
+/// language, e.g.
+/// `status == 200 && latency < 300ms && body contains "ok"`.
+///
+/// The available values are `status` (number), `latency` (duration), `size`
+/// (number of bytes of the body), `body` (string) and `header("Name")`
+/// (string, empty if the header is missing), along with number, duration
+/// (e.g. `250us`, `300ms`, `1.5s`, `2m`, `1h`) and string literals.
+///
+/// The operators are, from the lowest to the highest precedence: `||`, `&&`,
+/// the comparisons (`==`, `!=`, `<`, `<=`, `>`, `>=`, `contains` and
+/// `matches`, whose right side must be a regular expression literal) and
+/// `!`. Parentheses can be used for grouping.
+///
+/// Assertions are parsed and type-checked once, when created from a string.
+#[derive(Debug, Clone)]
+pub struct Assertion {
+    source: String,
+    expr: Expr,
+    needs_body: bool,
+}
+
+/// The parts of a response an assertion can check
+pub struct Response<'a> {
+    pub status: u16,
+    /// Time until the response headers were received
+    pub latency: Duration,
+    pub headers: &'a HeaderMap,
+    /// Body of the response, empty if it was not read
+    pub body: &'a str,
+}
+
+impl Assertion {
+    /// Whether evaluating the assertion requires the body of the response
+    pub fn needs_body(&self) -> bool {
+        self.needs_body
+    }
+
+    /// Whether the response satisfies the assertion
+    pub fn check(&self, response: &Response) -> bool {
+        matches!(self.expr.eval(response), Value::Bool(true))
+    }
+}
+
+impl std::str::FromStr for Assertion {
+    type Err = InquisitorError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let error = |reason: String| InquisitorError::InvalidAssertion(source.to_string(), reason);
+
+        let tokens = tokenize(source).map_err(error)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            needs_body: false,
+        };
+        let (expr, ty) = parser.or().map_err(error)?;
+
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(error(format!("unexpected {}", token)));
+        }
+        if ty != Type::Bool {
+            return Err(error(format!("expected a condition, found a {}", ty)));
+        }
+
+        Ok(Self {
+            source: source.trim().to_string(),
+            expr,
+            needs_body: parser.needs_body,
+        })
+    }
+}
+
+impl std::fmt::Display for Assertion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Duration(Duration),
+    Str(String),
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Cmp(CmpOp),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::Ident(ident) => write!(f, "'{}'", ident),
+            Self::Number(n) => write!(f, "number {}", n),
+            Self::Duration(d) => write!(f, "duration {:?}", d),
+            Self::Str(s) => write!(f, "string {:?}", s),
+            Self::LParen => write!(f, "'('"),
+            Self::RParen => write!(f, "')'"),
+            Self::And => write!(f, "'&&'"),
+            Self::Or => write!(f, "'||'"),
+            Self::Not => write!(f, "'!'"),
+            Self::Cmp(op) => write!(f, "'{}'", op),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl std::fmt::Display for CmpOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let op = match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let token = match c {
+            '(' => {
+                chars.next();
+                Token::LParen
+            }
+            ')' => {
+                chars.next();
+                Token::RParen
+            }
+            '&' | '|' | '=' => {
+                chars.next();
+                if chars.next() != Some(c) {
+                    return Err(format!("expected '{}{}'", c, c));
+                }
+                match c {
+                    '&' => Token::And,
+                    '|' => Token::Or,
+                    _ => Token::Cmp(CmpOp::Eq),
+                }
+            }
+            '!' | '<' | '>' => {
+                chars.next();
+                let or_equal = chars.next_if_eq(&'=').is_some();
+                match (c, or_equal) {
+                    ('!', false) => Token::Not,
+                    ('!', true) => Token::Cmp(CmpOp::Ne),
+                    ('<', false) => Token::Cmp(CmpOp::Lt),
+                    ('<', true) => Token::Cmp(CmpOp::Le),
+                    ('>', false) => Token::Cmp(CmpOp::Gt),
+                    _ => Token::Cmp(CmpOp::Ge),
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => s.push(c),
+                            // kept as is, e.g. for escapes in regexes
+                            Some(c) => {
+                                s.push('\\');
+                                s.push(c);
+                            }
+                            None => return Err("unterminated string".to_string()),
+                        },
+                        Some(c) => s.push(c),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                Token::Str(s)
+            }
+            c if c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                }
+                let mut unit = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                    unit.push(c);
+                }
+
+                let number: f64 = number
+                    .parse()
+                    .map_err(|_| format!("invalid number '{}'", number))?;
+                let micros = match unit.as_str() {
+                    "" => None,
+                    "us" => Some(1.0),
+                    "ms" => Some(1_000.0),
+                    "s" => Some(1_000_000.0),
+                    "m" => Some(60.0 * 1_000_000.0),
+                    "h" => Some(60.0 * 60.0 * 1_000_000.0),
+                    _ => return Err(format!("unknown time unit '{}'", unit)),
+                };
+
+                match micros {
+                    Some(micros) => {
+                        Token::Duration(Duration::from_micros((number * micros) as u64))
+                    }
+                    None => Token::Number(number),
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    ident.push(c);
+                }
+                Token::Ident(ident)
+            }
+            c => return Err(format!("unexpected character '{}'", c)),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Type {
+    Bool,
+    Number,
+    Duration,
+    Str,
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let ty = match self {
+            Self::Bool => "condition",
+            Self::Number => "number",
+            Self::Duration => "duration",
+            Self::Str => "string",
+        };
+        write!(f, "{}", ty)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Status,
+    Latency,
+    Size,
+    Body,
+    Header(HeaderName),
+    Number(f64),
+    Duration(Duration),
+    Str(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Cmp(Box<Expr>, CmpOp, Box<Expr>),
+    Contains(Box<Expr>, Box<Expr>),
+    Matches(Box<Expr>, regex::Regex),
+}
+
+enum Value<'a> {
+    Bool(bool),
+    Number(f64),
+    Duration(Duration),
+    Str(std::borrow::Cow<'a, str>),
+}
+
+impl Expr {
+    fn eval<'a>(&'a self, response: &'a Response) -> Value<'a> {
+        match self {
+            Self::Status => Value::Number(response.status as f64),
+            Self::Latency => Value::Duration(response.latency),
+            Self::Size => Value::Number(response.body.len() as f64),
+            Self::Body => Value::Str(response.body.into()),
+            Self::Header(name) => Value::Str(
+                response
+                    .headers
+                    .get(name)
+                    .map(|value| String::from_utf8_lossy(value.as_bytes()))
+                    .unwrap_or_default(),
+            ),
+            Self::Number(n) => Value::Number(*n),
+            Self::Duration(d) => Value::Duration(*d),
+            Self::Str(s) => Value::Str(s.into()),
+            Self::Not(expr) => Value::Bool(!expr.eval(response).as_bool()),
+            Self::And(a, b) => {
+                Value::Bool(a.eval(response).as_bool() && b.eval(response).as_bool())
+            }
+            Self::Or(a, b) => Value::Bool(a.eval(response).as_bool() || b.eval(response).as_bool()),
+            Self::Cmp(a, op, b) => {
+                let ordering = match (a.eval(response), b.eval(response)) {
+                    (Value::Number(a), Value::Number(b)) => a.partial_cmp(&b),
+                    (Value::Duration(a), Value::Duration(b)) => a.partial_cmp(&b),
+                    (Value::Str(a), Value::Str(b)) => a.partial_cmp(&b),
+                    (Value::Bool(a), Value::Bool(b)) => a.partial_cmp(&b),
+                    _ => unreachable!("operands are type-checked"),
+                };
+                Value::Bool(ordering.is_some_and(|ordering| match op {
+                    CmpOp::Eq => ordering.is_eq(),
+                    CmpOp::Ne => ordering.is_ne(),
+                    CmpOp::Lt => ordering.is_lt(),
+                    CmpOp::Le => ordering.is_le(),
+                    CmpOp::Gt => ordering.is_gt(),
+                    CmpOp::Ge => ordering.is_ge(),
+                }))
+            }
+            Self::Contains(a, b) => match (a.eval(response), b.eval(response)) {
+                (Value::Str(a), Value::Str(b)) => Value::Bool(a.contains(b.as_ref())),
+                _ => unreachable!("operands are type-checked"),
+            },
+            Self::Matches(a, regex) => match a.eval(response) {
+                Value::Str(a) => Value::Bool(regex.is_match(&a)),
+                _ => unreachable!("operands are type-checked"),
+            },
+        }
+    }
+}
+
+impl Value<'_> {
+    fn as_bool(&self) -> bool {
+        match self {
+            Self::Bool(b) => *b,
+            _ => unreachable!("operands are type-checked"),
+        }
+    }
+}
+
+/// Recursive descent parser, which also checks the types of the operands
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    needs_body: bool,
+}
+
+type Parsed = Result<(Expr, Type), String>;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| "unexpected end of the assertion".to_string())?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.peek() {
+            Some(token) if *token == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(token) => Err(format!("expected {}, found {}", expected, token)),
+            None => Err(format!("expected {}, found the end", expected)),
+        }
+    }
+
+    fn or(&mut self) -> Parsed {
+        let (mut expr, ty) = self.and()?;
+
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            let (rhs, rhs_ty) = self.and()?;
+            check_type("||", Type::Bool, ty)?;
+            check_type("||", Type::Bool, rhs_ty)?;
+            expr = Expr::Or(Box::new(expr), Box::new(rhs));
+        }
+
+        Ok((expr, ty))
+    }
+
+    fn and(&mut self) -> Parsed {
+        let (mut expr, ty) = self.comparison()?;
+
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            let (rhs, rhs_ty) = self.comparison()?;
+            check_type("&&", Type::Bool, ty)?;
+            check_type("&&", Type::Bool, rhs_ty)?;
+            expr = Expr::And(Box::new(expr), Box::new(rhs));
+        }
+
+        Ok((expr, ty))
+    }
+
+    fn comparison(&mut self) -> Parsed {
+        let (lhs, ty) = self.unary()?;
+
+        match self.peek().cloned() {
+            Some(Token::Cmp(op)) => {
+                self.pos += 1;
+                let (rhs, rhs_ty) = self.unary()?;
+                let ordered = !matches!(op, CmpOp::Eq | CmpOp::Ne);
+
+                if ty != rhs_ty {
+                    return Err(format!("cannot compare a {} with a {}", ty, rhs_ty));
+                }
+                if ordered && !matches!(ty, Type::Number | Type::Duration) {
+                    return Err(format!("'{}' expects numbers or durations", op));
+                }
+
+                Ok((Expr::Cmp(Box::new(lhs), op, Box::new(rhs)), Type::Bool))
+            }
+            Some(Token::Ident(ident)) if ident == "contains" => {
+                self.pos += 1;
+                let (rhs, rhs_ty) = self.unary()?;
+                check_type("contains", Type::Str, ty)?;
+                check_type("contains", Type::Str, rhs_ty)?;
+
+                Ok((Expr::Contains(Box::new(lhs), Box::new(rhs)), Type::Bool))
+            }
+            Some(Token::Ident(ident)) if ident == "matches" => {
+                self.pos += 1;
+                check_type("matches", Type::Str, ty)?;
+                let regex = match self.next()? {
+                    Token::Str(regex) => regex::Regex::new(&regex)
+                        .map_err(|e| format!("invalid regex '{}': {}", regex, e))?,
+                    token => {
+                        return Err(format!("'matches' expects a regex string, found {}", token))
+                    }
+                };
+
+                Ok((Expr::Matches(Box::new(lhs), regex), Type::Bool))
+            }
+            _ => Ok((lhs, ty)),
+        }
+    }
+
+    fn unary(&mut self) -> Parsed {
+        match self.next()? {
+            Token::Not => {
+                let (expr, ty) = self.unary()?;
+                check_type("!", Type::Bool, ty)?;
+                Ok((Expr::Not(Box::new(expr)), Type::Bool))
+            }
+            Token::LParen => {
+                let parsed = self.or()?;
+                self.expect(Token::RParen)?;
+                Ok(parsed)
+            }
+            Token::Number(n) => Ok((Expr::Number(n), Type::Number)),
+            Token::Duration(d) => Ok((Expr::Duration(d), Type::Duration)),
+            Token::Str(s) => Ok((Expr::Str(s), Type::Str)),
+            Token::Ident(ident) => match ident.as_str() {
+                "status" => Ok((Expr::Status, Type::Number)),
+                "latency" => Ok((Expr::Latency, Type::Duration)),
+                "size" => {
+                    self.needs_body = true;
+                    Ok((Expr::Size, Type::Number))
+                }
+                "body" => {
+                    self.needs_body = true;
+                    Ok((Expr::Body, Type::Str))
+                }
+                "header" => {
+                    self.expect(Token::LParen)?;
+                    let name = match self.next()? {
+                        Token::Str(name) => HeaderName::from_bytes(name.as_bytes())
+                            .map_err(|_| format!("invalid header name '{}'", name))?,
+                        token => return Err(format!("expected a header name, found {}", token)),
+                    };
+                    self.expect(Token::RParen)?;
+                    Ok((Expr::Header(name), Type::Str))
+                }
+                _ => Err(format!("unknown identifier '{}'", ident)),
+            },
+            token => Err(format!("unexpected {}", token)),
+        }
+    }
+}
+
+fn check_type(op: &str, expected: Type, found: Type) -> Result<(), String> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(format!(
+            "'{}' expects a {}, found a {}",
+            op, expected, found
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn check(assertion: &str, response: &Response) -> bool {
+        assertion.parse::<Assertion>().unwrap().check(response)
+    }
+
+    fn error(assertion: &str) -> String {
+        assertion.parse::<Assertion>().unwrap_err().to_string()
+    }
+
+    fn with_response(f: impl FnOnce(&Response)) {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-backend", HeaderValue::from_static("eu-1"));
+        let response = Response {
+            status: 200,
+            latency: Duration::from_millis(120),
+            headers: &headers,
+            body: "{\"status\": \"ok\"}",
+        };
+        f(&response);
+    }
+
+    #[test]
+    fn tokenizes() {
+        assert_eq!(
+            tokenize("status>=200&&!(body contains \"a\\\"b\")||latency<1.5s").unwrap(),
+            vec![
+                Token::Ident("status".to_string()),
+                Token::Cmp(CmpOp::Ge),
+                Token::Number(200.0),
+                Token::And,
+                Token::Not,
+                Token::LParen,
+                Token::Ident("body".to_string()),
+                Token::Ident("contains".to_string()),
+                Token::Str("a\"b".to_string()),
+                Token::RParen,
+                Token::Or,
+                Token::Ident("latency".to_string()),
+                Token::Cmp(CmpOp::Lt),
+                Token::Duration(Duration::from_millis(1500)),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_durations() {
+        let duration = |s: &str| match tokenize(s).unwrap()[..] {
+            [Token::Duration(d)] => d,
+            _ => panic!("not a duration: {}", s),
+        };
+
+        assert_eq!(duration("250us"), Duration::from_micros(250));
+        assert_eq!(duration("300ms"), Duration::from_millis(300));
+        assert_eq!(duration("2s"), Duration::from_secs(2));
+        assert_eq!(duration("1.5m"), Duration::from_secs(90));
+        assert_eq!(duration("1h"), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn evaluates_values() {
+        with_response(|res| {
+            assert!(check("status == 200", res));
+            assert!(!check("status == 404", res));
+            assert!(check("latency == 120ms", res));
+            assert!(check("size == 16", res));
+            assert!(check("body == \"{\\\"status\\\": \\\"ok\\\"}\"", res));
+            assert!(check("header(\"X-Backend\") == \"eu-1\"", res));
+            assert!(check("header(\"X-Missing\") == \"\"", res));
+        });
+    }
+
+    #[test]
+    fn evaluates_comparisons() {
+        with_response(|res| {
+            assert!(check("status != 500", res));
+            assert!(check("status < 300", res));
+            assert!(check("status <= 200", res));
+            assert!(check("status > 199", res));
+            assert!(check("status >= 200", res));
+            assert!(!check("status > 200", res));
+            assert!(check("latency < 300ms", res));
+            assert!(check("latency >= 120000us", res));
+            assert!(check("latency > 0.1s", res));
+            assert!(check("\"abc\" != \"abd\"", res));
+        });
+    }
+
+    #[test]
+    fn evaluates_string_operators() {
+        with_response(|res| {
+            assert!(check("body contains \"ok\"", res));
+            assert!(!check("body contains \"error\"", res));
+            assert!(check("header(\"x-backend\") contains \"eu\"", res));
+            assert!(check("body matches \"\\\"status\\\":\\s*\\\"ok\\\"\"", res));
+            assert!(check("header(\"x-backend\") matches \"^eu-\\d+$\"", res));
+            assert!(!check("body matches \"^error\"", res));
+        });
+    }
+
+    #[test]
+    fn evaluates_logical_operators() {
+        with_response(|res| {
+            assert!(check(
+                "status == 200 && latency < 300ms && body contains \"ok\"",
+                res
+            ));
+            assert!(!check("status == 200 && latency < 100ms", res));
+            assert!(check("status == 404 || body contains \"ok\"", res));
+            assert!(!check("status == 404 || size == 0", res));
+            assert!(check("!(status == 404)", res));
+            assert!(check("!!(status == 200)", res));
+            // && binds tighter than ||
+            assert!(check("status == 200 || status == 404 && size == 0", res));
+            assert!(!check("(status == 200 || status == 404) && size == 0", res));
+        });
+    }
+
+    #[test]
+    fn tracks_body_usage() {
+        let needs_body = |s: &str| s.parse::<Assertion>().unwrap().needs_body();
+
+        assert!(!needs_body("status == 200 && latency < 1s"));
+        assert!(!needs_body("header(\"x\") == \"y\""));
+        assert!(needs_body("size > 0"));
+        assert!(needs_body("status == 200 || body contains \"ok\""));
+    }
+
+    #[test]
+    fn displays_source() {
+        let assertion: Assertion = "  status == 200 ".parse().unwrap();
+        assert_eq!(assertion.to_string(), "status == 200");
+    }
+
+    #[test]
+    fn rejects_syntax_errors() {
+        assert!(error("").contains("unexpected end"));
+        assert!(error("status = 200").contains("expected '=='"));
+        assert!(error("status == 200 &").contains("expected '&&'"));
+        assert!(error("(status == 200").contains("expected ')'"));
+        assert!(error("status == 200)").contains("unexpected ')'"));
+        assert!(error("body contains \"ok").contains("unterminated string"));
+        assert!(error("status == 200 $").contains("unexpected character '$'"));
+        assert!(error("latency < 3days").contains("unknown time unit 'days'"));
+        assert!(error("status == 1.2.3").contains("invalid number"));
+        assert!(error("code == 200").contains("unknown identifier 'code'"));
+        assert!(error("header(x) == \"\"").contains("expected a header name"));
+        assert!(error("header(\"a b\") == \"\"").contains("invalid header name"));
+        assert!(error("body matches body").contains("expects a regex string"));
+        assert!(error("body matches \"(\"").contains("invalid regex"));
+    }
+
+    #[test]
+    fn rejects_type_errors() {
+        assert!(error("status").contains("expected a condition, found a number"));
+        assert!(error("status == \"200\"").contains("cannot compare a number with a string"));
+        assert!(error("latency < 300").contains("cannot compare a duration with a number"));
+        assert!(error("body < \"a\"").contains("'<' expects numbers or durations"));
+        assert!(error("status contains \"2\"").contains("'contains' expects a string"));
+        assert!(error("size matches \"1\"").contains("'matches' expects a string"));
+        assert!(error("status == 200 && size").contains("'&&' expects a condition"));
+        assert!(error("body || status == 200").contains("'||' expects a condition"));
+        assert!(error("!status").contains("'!' expects a condition"));
+    }
+
+    #[test]
+    fn reports_the_assertion_in_errors() {
+        assert_eq!(
+            error("code == 200"),
+            "invalid assertion 'code == 200': unknown identifier 'code'"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use crate::error::InquisitorError;
+
+/// Cookie sent with the requests, given as `NAME=VALUE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+}
+
+impl std::str::FromStr for Cookie {
+    type Err = InquisitorError;
+
+    fn from_str(cookie: &str) -> Result<Self, Self::Err> {
+        match cookie.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => Ok(Self {
+                name: name.trim().to_string(),
+                value: value.trim().to_string(),
+            }),
+            _ => Err(InquisitorError::InvalidCookie(cookie.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Cookie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+/// Value of the `Cookie` header sending the given cookies
+pub fn header(cookies: &[Cookie]) -> String {
+    cookies
+        .iter()
+        .map(Cookie::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cookies() {
+        assert_eq!(
+            "session=abc=1".parse::<Cookie>().unwrap(),
+            Cookie {
+                name: "session".to_string(),
+                value: "abc=1".to_string()
+            }
+        );
+        assert_eq!("empty=".parse::<Cookie>().unwrap().value, "");
+        assert!("session".parse::<Cookie>().is_err());
+        assert!("=abc".parse::<Cookie>().is_err());
+    }
+
+    #[test]
+    fn joins_cookies_into_header() {
+        let cookies = [
+            "session=abc".parse().unwrap(),
+            "theme=dark".parse().unwrap(),
+        ];
+        assert_eq!(header(&cookies), "session=abc; theme=dark");
+    }
+}
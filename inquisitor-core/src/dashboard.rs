@@ -0,0 +1,247 @@
+use crate::config::Until;
+use crate::time::Microseconds;
+use crate::window::Window;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::widgets::{Block, Gauge, Paragraph, Sparkline};
+use ratatui::Frame;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Interval between two redraws of the dashboard
+const REDRAW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Interval over which the rate and the latency percentiles are measured
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of rate samples kept for the sparkline
+const MAX_SAMPLES: usize = 600;
+
+/// Counters of a run shown by the dashboard, shared with the connections
+pub(crate) struct LiveStats {
+    pub url: String,
+    pub start: Instant,
+    pub warmup: Duration,
+    pub until: Until,
+    pub iterations: usize,
+    /// Duration of the test, in microseconds
+    pub duration: u64,
+    pub passes: Arc<AtomicUsize>,
+    pub errors: Arc<AtomicUsize>,
+    /// Response times of the requests completed since the last sample
+    pub window: Window,
+    /// Set when the user quits from the dashboard, to stop the test
+    pub should_exit: Arc<AtomicBool>,
+}
+
+impl LiveStats {
+    fn requests(&self) -> usize {
+        self.passes.load(Ordering::Relaxed) + self.errors.load(Ordering::Relaxed)
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed().saturating_sub(self.warmup)
+    }
+}
+
+/// Live dashboard drawn in the terminal (in an alternate screen) by its own
+/// thread during the run
+pub(crate) struct Dashboard {
+    done: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl Dashboard {
+    pub fn start(stats: LiveStats) -> Self {
+        let done = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let done = done.clone();
+            std::thread::spawn(move || draw_until_done(stats, done))
+        };
+
+        Self { done, thread }
+    }
+
+    /// Stop drawing, and restore the terminal
+    pub fn stop(self) {
+        self.done.store(true, Ordering::Relaxed);
+        self.thread.join().expect("dashboard thread panicked");
+    }
+}
+
+/// Measurements of the last intervals
+struct Samples {
+    /// Requests per second in each interval, the last one at the end
+    rps: VecDeque<u64>,
+    /// p50, p90 and p99 latencies of the last interval with requests
+    percentiles: Option<[u64; 3]>,
+    at: Instant,
+    requests: usize,
+}
+
+impl Samples {
+    fn new(start: Instant) -> Self {
+        Self {
+            rps: VecDeque::new(),
+            percentiles: None,
+            at: start,
+            requests: 0,
+        }
+    }
+
+    /// Take a new sample if the last one is old enough
+    fn update(&mut self, stats: &LiveStats) {
+        let since = self.at.elapsed();
+        if since < SAMPLE_INTERVAL {
+            return;
+        }
+
+        let requests = stats.requests();
+        let rps = requests.saturating_sub(self.requests) as f64 / since.as_secs_f64();
+        if self.rps.len() == MAX_SAMPLES {
+            self.rps.pop_front();
+        }
+        self.rps.push_back(rps.round() as u64);
+
+        let window = stats.window.take();
+        if !window.is_empty() {
+            self.percentiles = Some([0.5, 0.9, 0.99].map(|q| window.value_at_quantile(q)));
+        }
+
+        self.at = Instant::now();
+        self.requests = requests;
+    }
+}
+
+fn draw_until_done(stats: LiveStats, done: Arc<AtomicBool>) {
+    let mut terminal = ratatui::init();
+    let mut samples = Samples::new(stats.start);
+
+    while !done.load(Ordering::Relaxed) {
+        samples.update(&stats);
+        // failing to draw (e.g. if the terminal went away) must not stop the
+        // test, which still prints its results at the end
+        let _ = terminal.draw(|frame| render(frame, &stats, &samples));
+
+        // waiting for input also paces the redraws
+        if let Ok(true) = event::poll(REDRAW_INTERVAL) {
+            if let Ok(Event::Key(key)) = event::read() {
+                // Ctrl+C does not raise a signal in raw mode
+                let ctrl_c =
+                    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press && (ctrl_c || key.code == KeyCode::Char('q')) {
+                    stats.should_exit.store(true, Ordering::SeqCst);
+                }
+            }
+        }
+    }
+
+    ratatui::restore();
+}
+
+fn render(frame: &mut Frame, stats: &LiveStats, samples: &Samples) {
+    let elapsed = stats.elapsed();
+    let requests = stats.requests();
+    let errors = stats.errors.load(Ordering::Relaxed);
+
+    let [title, gauge, numbers, sparkline] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(3),
+        Constraint::Length(4),
+        Constraint::Min(3),
+    ])
+    .areas(frame.area());
+
+    frame.render_widget(
+        Paragraph::new(format!("{} (press q or Ctrl+C to stop)", stats.url)),
+        title,
+    );
+
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::bordered().title("progress"))
+            .ratio(progress(
+                stats.until,
+                requests,
+                stats.iterations,
+                elapsed.as_micros() as u64,
+                stats.duration,
+            ))
+            .label(format!(
+                "{:.1} s, {} requests",
+                elapsed.as_secs_f64(),
+                requests
+            )),
+        gauge,
+    );
+
+    let latencies = match samples.percentiles {
+        Some(percentiles) => {
+            let [p50, p90, p99] = percentiles.map(|p| Microseconds(p as f64));
+            format!("p50: {}, p90: {}, p99: {}", p50, p90, p99)
+        }
+        None => "-".to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(vec![
+            format!(
+                "rps: {}, errors: {} ({:.2}%)",
+                samples.rps.back().copied().unwrap_or_default(),
+                errors,
+                100.0 * errors as f64 / requests.max(1) as f64
+            )
+            .into(),
+            format!("latencies: {}", latencies).into(),
+        ])
+        .block(Block::bordered().title("last second")),
+        numbers,
+    );
+
+    // only the most recent samples fit
+    let width = sparkline.width.saturating_sub(2) as usize;
+    let rps: Vec<_> = samples
+        .rps
+        .iter()
+        .skip(samples.rps.len().saturating_sub(width))
+        .copied()
+        .collect();
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::bordered().title("requests per second"))
+            .data(&rps),
+        sparkline,
+    );
+}
+
+/// Fraction of the test that is done, given the requests completed and the
+/// time elapsed (in microseconds), and their limits
+fn progress(until: Until, requests: usize, iterations: usize, elapsed: u64, duration: u64) -> f64 {
+    // limits that do not apply are 0 or the maximum value, which count as
+    // already reached or never reached
+    let ratio = |done: f64, limit: f64| (done / limit).min(1.0);
+    let by_iterations = ratio(requests as f64, iterations as f64);
+    let by_duration = ratio(elapsed as f64, duration as f64);
+
+    match until {
+        Until::Either => by_iterations.max(by_duration),
+        Until::Both => by_iterations.min(by_duration),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_progress_toward_limits() {
+        assert_eq!(progress(Until::Either, 50, 100, 0, u64::MAX), 0.5);
+        assert_eq!(progress(Until::Either, 10, usize::MAX, 750, 1000), 0.75);
+        assert_eq!(progress(Until::Either, 50, 100, 750, 1000), 0.75);
+        assert_eq!(progress(Until::Either, 200, 100, 0, 1000), 1.0);
+        assert_eq!(progress(Until::Both, 50, 100, 750, 1000), 0.5);
+        assert_eq!(progress(Until::Both, 0, 0, 250, 1000), 0.25);
+    }
+}
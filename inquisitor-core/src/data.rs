@@ -0,0 +1,165 @@
+use crate::error::InquisitorError;
+
+/// Rows of request parameters, read from a CSV file whose first row names the
+/// columns
+#[derive(Debug, PartialEq)]
+pub struct DataFile {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Error in a CSV data file, with the number of the line (starting at 1)
+/// where it was found
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl ParseError {
+    fn new(line: usize, reason: impl Into<String>) -> Self {
+        Self {
+            line,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl DataFile {
+    /// Read and parse a CSV data file
+    pub fn load(path: &str) -> Result<Self, InquisitorError> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| InquisitorError::DataFile(path.to_string(), e))?;
+
+        parse(&source).map_err(|e| InquisitorError::InvalidDataFile(path.to_string(), e))
+    }
+
+    /// Row at the given index, cycling back to the first row after the last
+    pub fn row(&self, index: usize) -> &[String] {
+        &self.rows[index % self.rows.len()]
+    }
+}
+
+/// Parse CSV records separated by commas, with fields optionally quoted by
+/// `"` (to include commas, quotes as `""`, or line breaks). The first record
+/// names the columns, and all others must have as many fields.
+pub fn parse(source: &str) -> Result<DataFile, ParseError> {
+    let mut records = records(source)?.into_iter();
+
+    let (_, columns) = records
+        .next()
+        .ok_or_else(|| ParseError::new(1, "no header row found"))?;
+    for (i, column) in columns.iter().enumerate() {
+        if column.is_empty() {
+            return Err(ParseError::new(1, format!("column {} has no name", i + 1)));
+        }
+        if columns[..i].contains(column) {
+            return Err(ParseError::new(1, format!("duplicate column '{}'", column)));
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (line, row) in records {
+        if row.len() != columns.len() {
+            return Err(ParseError::new(
+                line,
+                format!("expected {} fields, found {}", columns.len(), row.len()),
+            ));
+        }
+        rows.push(row);
+    }
+
+    if rows.is_empty() {
+        return Err(ParseError::new(2, "no data rows found"));
+    }
+
+    Ok(DataFile { columns, rows })
+}
+
+/// Split the source into records, with the line where each one starts.
+/// Blank lines are skipped.
+fn records(source: &str) -> Result<Vec<(usize, Vec<String>)>, ParseError> {
+    let mut records = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let start = line;
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted => {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        quoted = false;
+                    }
+                }
+                '"' if field.is_empty() => quoted = true,
+                '\n' if quoted => {
+                    line += 1;
+                    field.push(c);
+                }
+                '\n' => {
+                    line += 1;
+                    break;
+                }
+                ',' if !quoted => record.push(std::mem::take(&mut field)),
+                '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+                c => field.push(c),
+            }
+        }
+
+        if quoted {
+            return Err(ParseError::new(start, "unterminated quoted field"));
+        }
+        if record.is_empty() && field.trim().is_empty() {
+            continue;
+        }
+
+        record.push(field);
+        records.push((start, record));
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_records() {
+        let data = parse("id,token\n1,abc\n\n2,\"d,e \"\"f\"\"\"\r\n3,\"multi\nline\"\n").unwrap();
+
+        assert_eq!(data.columns, vec!["id", "token"]);
+        assert_eq!(
+            data.rows,
+            vec![
+                vec!["1", "abc"],
+                vec!["2", "d,e \"f\""],
+                vec!["3", "multi\nline"],
+            ]
+        );
+        assert_eq!(data.row(4), ["2", "d,e \"f\""]);
+    }
+
+    #[test]
+    fn reports_line_of_errors() {
+        let error = |source| parse(source).unwrap_err();
+
+        assert_eq!(error(""), ParseError::new(1, "no header row found"));
+        assert_eq!(error("id\n"), ParseError::new(2, "no data rows found"));
+        assert_eq!(error("id,id\n1,2\n").line, 1);
+        assert_eq!(error("id,\n1,2\n").line, 1);
+        assert_eq!(
+            error("id,token\n1,a\n2\n"),
+            ParseError::new(3, "expected 2 fields, found 1")
+        );
+        assert_eq!(error("id\n\"a\nb\n").line, 2);
+    }
+}
@@ -1,13 +1,154 @@
 /// Error type for this library
 #[derive(Debug)]
 pub enum InquisitorError {
-    DurationParseError,
+    /// The duration is not one or more numbers followed by a time unit
+    DurationParseError(String),
+    /// The HTTP method contains characters not allowed in a method token
+    InvalidMethod(String),
+    /// The assertion (first field) could not be parsed, for the given reason
+    InvalidAssertion(String, String),
+    /// The request body file (first field) could not be read
+    RequestBodyFile(String, std::io::Error),
+    /// Both a request body and a request body file were given
+    ConflictingRequestBody,
+    /// The stage is not in the DURATION:CONNECTIONS[:RATE] format
+    InvalidStage(String),
+    /// The ordinal buckets are not an ascending list of `N`, `N-M` or `N+`
+    InvalidOrdinalBuckets(String),
+    /// The HTTP file (first field) could not be read
+    HttpFile(String, std::io::Error),
+    /// The HTTP file (first field) is malformed
+    InvalidHttpFile(String, crate::http_file::ParseError),
+    /// The client identity (first field) could not be loaded, for the given
+    /// reason
+    ClientIdentity(String, String),
+    /// The CA certificate (first field) could not be loaded, for the given
+    /// reason
+    CaCert(String, String),
+    /// A CA certificate was given with `insecure`, which ignores it, and
+    /// `strict_tls_config` forbids that
+    InsecureCaCert,
+    /// The HTTP client could not be built, for the given reason
+    ClientBuild(String),
+    /// The target URL (first field) is not valid, for the given reason
+    InvalidUrl(String, String),
+    /// The regular expression (first field) is not valid, for the given
+    /// reason
+    InvalidRegex(String, String),
+    /// The proxy URL (first field) is not valid, for the given reason
+    InvalidProxy(String, String),
+    /// The placeholder (first field) is malformed, for the given reason
+    InvalidPlaceholder(String, String),
+    /// The data file (first field) could not be read
+    DataFile(String, std::io::Error),
+    /// The data file (first field) is malformed
+    InvalidDataFile(String, crate::data::ParseError),
+    /// The retry condition is not a status code, a status class or io-error
+    InvalidRetryOn(String),
+    /// The expected statuses are not a list of status codes and ranges
+    InvalidStatusSet(String),
+    /// The cookie is not in the NAME=VALUE format
+    InvalidCookie(String),
+    /// The percentile is not a number strictly between 0 and 100
+    InvalidPercentile(String),
+    /// The threshold (first field) is malformed, for the given reason
+    InvalidThreshold(String, String),
+    /// The configuration cannot be run, for the given reasons
+    InvalidConfig(Vec<String>),
+    /// The results could not be written to the output file (first field)
+    OutputFile(String, std::io::Error),
+    /// The metrics endpoint could not listen on the address (first field)
+    MetricsAddr(String, std::io::Error),
+    /// Metrics could not be sent to the StatsD server (first field)
+    StatsdAddr(String, std::io::Error),
 }
 
 impl std::fmt::Display for InquisitorError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
-            Self::DurationParseError => write!(f, ""),
+            Self::DurationParseError(duration) => write!(
+                f,
+                "invalid duration '{}': expected a number followed by ms, s, m, h or d, e.g. 10s \
+                 or 1m30s",
+                duration
+            ),
+            Self::InvalidMethod(method) => write!(f, "invalid HTTP method '{}'", method),
+            Self::InvalidAssertion(assertion, reason) => {
+                write!(f, "invalid assertion '{}': {}", assertion, reason)
+            }
+            Self::RequestBodyFile(path, e) => {
+                write!(f, "could not read request body file {}: {}", path, e)
+            }
+            Self::ConflictingRequestBody => {
+                write!(
+                    f,
+                    "a request body and a request body file cannot both be given"
+                )
+            }
+            Self::InvalidStage(stage) => write!(
+                f,
+                "invalid stage '{}': expected DURATION:CONNECTIONS[:RATE], e.g. 2m:10",
+                stage
+            ),
+            Self::InvalidOrdinalBuckets(buckets) => write!(
+                f,
+                "invalid ordinal buckets '{}': expected ascending N, N-M or N+, e.g. 1,2-10,11+",
+                buckets
+            ),
+            Self::HttpFile(path, e) => write!(f, "could not read HTTP file {}: {}", path, e),
+            Self::InvalidHttpFile(path, e) => {
+                write!(f, "invalid HTTP file {}:{}: {}", path, e.line, e.reason)
+            }
+            Self::ClientIdentity(path, reason) => {
+                write!(f, "could not load client identity {}: {}", path, reason)
+            }
+            Self::CaCert(path, reason) => {
+                write!(f, "could not load CA certificate {}: {}", path, reason)
+            }
+            Self::InsecureCaCert => write!(
+                f,
+                "--insecure disables certificate validation, --ca-cert will have no effect"
+            ),
+            Self::ClientBuild(reason) => write!(f, "could not build the HTTP client: {}", reason),
+            Self::InvalidUrl(url, reason) => write!(f, "invalid URL '{}': {}", url, reason),
+            Self::InvalidRegex(regex, reason) => {
+                write!(f, "invalid regular expression '{}': {}", regex, reason)
+            }
+            Self::InvalidProxy(url, reason) => write!(f, "invalid proxy '{}': {}", url, reason),
+            Self::InvalidPlaceholder(placeholder, reason) => {
+                write!(f, "invalid placeholder '{}': {}", placeholder, reason)
+            }
+            Self::DataFile(path, e) => write!(f, "could not read data file {}: {}", path, e),
+            Self::InvalidDataFile(path, e) => {
+                write!(f, "invalid data file {}:{}: {}", path, e.line, e.reason)
+            }
+            Self::InvalidStatusSet(statuses) => write!(
+                f,
+                "invalid status codes '{}': expected a comma-separated list of codes and ranges, e.g. 200,301-302",
+                statuses
+            ),
+            Self::InvalidCookie(cookie) => {
+                write!(f, "invalid cookie '{}': expected NAME=VALUE", cookie)
+            }
+            Self::InvalidPercentile(percentile) => write!(
+                f,
+                "invalid percentile '{}': expected a number between 0 and 100 (exclusive)",
+                percentile
+            ),
+            Self::InvalidThreshold(threshold, reason) => {
+                write!(f, "invalid threshold '{}': {}", threshold, reason)
+            }
+            Self::InvalidRetryOn(condition) => write!(
+                f,
+                "invalid retry condition '{}': expected a status code, a class such as 5xx, or io-error",
+                condition
+            ),
+            Self::InvalidConfig(problems) => {
+                write!(f, "invalid configuration: {}", problems.join(", "))
+            }
+            Self::OutputFile(path, e) => write!(f, "could not write results to {}: {}", path, e),
+            Self::MetricsAddr(addr, e) => write!(f, "could not serve metrics on {}: {}", addr, e),
+            Self::StatsdAddr(addr, e) => write!(f, "could not send metrics to {}: {}", addr, e),
         }
     }
 }
@@ -0,0 +1,141 @@
+use std::io::Write;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Version of the format of the events, included in each of them
+pub const EVENTS_SCHEMA: u32 = 1;
+
+/// Interval between two events with the statistics of the run so far
+pub const EVENT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of events waiting to be written from which new ones are dropped
+const EVENT_BUFFER: usize = 1024;
+
+/// Maximum time waited at the end of the run for the pending events to be
+/// written
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Maximum time waited for a reader to open the FIFO, after which no events
+/// are written
+const OPEN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Interval between two attempts at opening a FIFO without a reader
+const OPEN_RETRY: Duration = Duration::from_millis(20);
+
+/// Writes newline-delimited JSON events to a file or FIFO, from a separate
+/// thread so that a slow or missing reader never blocks the run (events are
+/// dropped instead)
+pub struct EventWriter {
+    /// Taken out when closing, which ends the writer thread
+    sender: Mutex<Option<SyncSender<String>>>,
+    done: Mutex<Receiver<()>>,
+    start: Instant,
+}
+
+impl EventWriter {
+    /// Start writing events to the given path. Opening it happens in the
+    /// writer thread, which waits for a bounded time for a FIFO to have a
+    /// reader.
+    pub fn open(path: String) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<String>(EVENT_BUFFER);
+        let (done_sender, done) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            match open(&path) {
+                Ok(mut file) => {
+                    for event in receiver {
+                        if writeln!(file, "{}", event).is_err() {
+                            // the reader is gone
+                            break;
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Warning: could not open event pipe {}: {}", path, e),
+            }
+
+            let _ = done_sender.send(());
+        });
+
+        Self {
+            sender: Mutex::new(Some(sender)),
+            done: Mutex::new(done),
+            start: Instant::now(),
+        }
+    }
+
+    /// Queue an event with the given name and fields (which must be a JSON
+    /// object), or drop it if the reader is not keeping up
+    pub fn emit(&self, event: &str, fields: serde_json::Value) {
+        let mut object = serde_json::json!({
+            "schema": EVENTS_SCHEMA,
+            "event": event,
+            "time_us": self.start.elapsed().as_micros() as u64,
+        });
+        if let (Some(object), serde_json::Value::Object(fields)) = (object.as_object_mut(), fields)
+        {
+            object.extend(fields);
+        }
+
+        if let Some(sender) = self.sender.lock().unwrap().as_ref() {
+            let _ = sender.try_send(object.to_string());
+        }
+    }
+
+    /// Stop accepting events and wait (for a bounded time) until the pending
+    /// ones are written
+    pub fn close(&self) {
+        self.sender.lock().unwrap().take();
+        let _ = self.done.lock().unwrap().recv_timeout(FLUSH_TIMEOUT);
+    }
+}
+
+/// Open the path for writing without blocking on a FIFO that has no reader:
+/// the opening is retried until there is one, or `OPEN_TIMEOUT` elapses
+#[cfg(unix)]
+fn open(path: &str) -> std::io::Result<std::fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::AsRawFd;
+
+    let deadline = Instant::now() + OPEN_TIMEOUT;
+    loop {
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path);
+
+        match file {
+            // a FIFO without a reader
+            Err(e) if e.raw_os_error() == Some(libc::ENXIO) => {
+                if Instant::now() >= deadline {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        "no reader",
+                    ));
+                }
+                std::thread::sleep(OPEN_RETRY);
+            }
+            Ok(file) => {
+                // writes block again, so that no event is cut short
+                // SAFETY: the descriptor is owned by `file`, which is alive
+                unsafe {
+                    let flags = libc::fcntl(file.as_raw_fd(), libc::F_GETFL);
+                    libc::fcntl(file.as_raw_fd(), libc::F_SETFL, flags & !libc::O_NONBLOCK);
+                }
+                return Ok(file);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn open(path: &str) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+}
@@ -0,0 +1,285 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Maximum number of distinct errors printed in the summary
+pub const MAX_ERRORS_PRINTED: usize = 10;
+
+/// Maximum number of distinct errors counted, the errors seen after that
+/// many others being counted together as `OTHER`
+pub const MAX_DISTINCT_ERRORS: usize = 100;
+
+/// Error recorded for the errors beyond `MAX_DISTINCT_ERRORS`
+pub const OTHER: &str = "other";
+
+/// Error recorded when a request takes longer than the timeout
+pub const TIMEOUT: &str = "timeout";
+
+/// Error recorded when a connection to the target could not be established
+/// (including when it took longer than the connect timeout)
+pub const CONNECT: &str = "connect error";
+
+/// Error recorded when the body of a response matches `failed_body`
+pub const FAILED_BODY: &str = "body matches --failed-body";
+
+/// Error recorded when the body of a response does not match `expect_body`
+pub const UNEXPECTED_BODY: &str = "body does not match --expect-body";
+
+/// Kind of a failed request, without its URL or anything received, so that
+/// the same failure is always counted as the same error, e.g. `request error
+/// (connection reset)`
+pub fn request_error(error: &reqwest::Error) -> String {
+    let kind = if error.is_redirect() {
+        "redirect error"
+    } else if error.is_body() {
+        "body error"
+    } else if error.is_decode() {
+        "decode error"
+    } else if error.is_builder() {
+        "builder error"
+    } else {
+        "request error"
+    };
+
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            return format!("{} ({})", kind, io.kind());
+        }
+        source = error.source();
+    }
+
+    kind.to_string()
+}
+
+/// Error for the body of a response with an expected status, if any. A body
+/// matching `failed` is an error even if it also matches `expected`.
+pub fn body_failure(
+    body: &str,
+    failed: Option<&Regex>,
+    expected: Option<&Regex>,
+) -> Option<&'static str> {
+    if failed.is_some_and(|failed| failed.is_match(body)) {
+        Some(FAILED_BODY)
+    } else if expected.is_some_and(|expected| !expected.is_match(body)) {
+        Some(UNEXPECTED_BODY)
+    } else {
+        None
+    }
+}
+
+/// Number of occurrences of each distinct error, up to `MAX_DISTINCT_ERRORS`
+/// of them (and `OTHER`)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ErrorCounts(HashMap<String, usize>);
+
+impl ErrorCounts {
+    /// Count one occurrence of the given error
+    pub fn record(&mut self, error: impl Into<String>) {
+        self.add(error.into(), 1);
+    }
+
+    /// Add the errors counted in `other` to these
+    pub fn merge(&mut self, other: ErrorCounts) {
+        for (error, count) in other.0 {
+            self.add(error, count);
+        }
+    }
+
+    fn add(&mut self, error: String, count: usize) {
+        let error = if self.0.len() >= MAX_DISTINCT_ERRORS && !self.0.contains_key(&error) {
+            OTHER.to_string()
+        } else {
+            error
+        };
+        *self.0.entry(error).or_default() += count;
+    }
+
+    /// Number of occurrences of the given error
+    pub fn get(&self, error: &str) -> usize {
+        self.0.get(error).copied().unwrap_or_default()
+    }
+
+    /// Total number of errors counted
+    pub fn total(&self) -> usize {
+        self.0.values().sum()
+    }
+
+    /// Whether no error was counted
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Errors sorted from the most to the least frequent
+    pub fn sorted(&self) -> Vec<(&str, usize)> {
+        let mut errors: Vec<_> = self.0.iter().map(|(e, c)| (e.as_str(), *c)).collect();
+        errors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        errors
+    }
+
+    /// Errors and their counts as a JSON object
+    pub fn to_json(&self) -> serde_json::Value {
+        self.0
+            .iter()
+            .map(|(error, count)| (error.clone(), (*count).into()))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+impl std::fmt::Display for ErrorCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let errors = self.sorted();
+        write!(f, "top errors:")?;
+
+        for (error, count) in errors.iter().take(MAX_ERRORS_PRINTED) {
+            write!(f, "\n\t{}\t{}", count, error)?;
+        }
+        if errors.len() > MAX_ERRORS_PRINTED {
+            write!(
+                f,
+                "\n\t({} other distinct errors)",
+                errors.len() - MAX_ERRORS_PRINTED
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Minimum time between two prints of the same error by a connection
+pub const ERROR_PRINT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Prints errors as they happen, each distinct error at most once per
+/// `ERROR_PRINT_INTERVAL`, so that printing does not slow the test down
+#[derive(Debug, Default)]
+pub struct ErrorPrinter {
+    /// When each error was last printed, and how many times it was
+    /// suppressed since
+    printed: HashMap<String, (Instant, usize)>,
+}
+
+impl ErrorPrinter {
+    /// Print the message about the error (as counted by `ErrorCounts`) to
+    /// stderr, unless the same error was printed recently
+    pub fn print(&mut self, error: &str, message: String) {
+        if let Some(line) = self.throttle(error, message, Instant::now()) {
+            eprintln!("{}", line);
+        }
+    }
+
+    /// Line to print for the message about the error at the given time, if
+    /// any
+    fn throttle(&mut self, error: &str, message: String, now: Instant) -> Option<String> {
+        match self.printed.get_mut(error) {
+            Some((last, suppressed)) if now < *last + ERROR_PRINT_INTERVAL => {
+                *suppressed += 1;
+                None
+            }
+            Some((last, suppressed)) => {
+                let line = match *suppressed {
+                    0 => message,
+                    n => format!("{} (+{} similar suppressed)", message, n),
+                };
+                *last = now;
+                *suppressed = 0;
+                Some(line)
+            }
+            None => {
+                self.printed.insert(error.to_string(), (now, 0));
+                Some(message)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_bodies() {
+        let failed = Regex::new("error").unwrap();
+        let expected = Regex::new("\"ok\"").unwrap();
+        let check = |body, failed, expected| body_failure(body, failed, expected);
+
+        assert_eq!(check("error", None, None), None);
+        assert_eq!(check("error", Some(&failed), None), Some(FAILED_BODY));
+        assert_eq!(check("fine", Some(&failed), None), None);
+        assert_eq!(check("\"ok\"", None, Some(&expected)), None);
+        assert_eq!(check("fine", None, Some(&expected)), Some(UNEXPECTED_BODY));
+
+        // with both, the failed body takes precedence
+        let both = |body| check(body, Some(&failed), Some(&expected));
+        assert_eq!(both("\"ok\""), None);
+        assert_eq!(both("\"ok\", error"), Some(FAILED_BODY));
+        assert_eq!(both("error"), Some(FAILED_BODY));
+        assert_eq!(both("fine"), Some(UNEXPECTED_BODY));
+    }
+
+    #[test]
+    fn throttles_printed_errors() {
+        let mut printer = ErrorPrinter::default();
+        let start = Instant::now();
+        let mut at = |message: &str, ms| {
+            let error = message.split(':').next().unwrap();
+            printer.throttle(
+                error,
+                message.to_string(),
+                start + Duration::from_millis(ms),
+            )
+        };
+
+        assert_eq!(at("refused", 0).as_deref(), Some("refused"));
+        assert_eq!(at("refused", 10), None);
+        assert_eq!(at("timeout", 20).as_deref(), Some("timeout"));
+        assert_eq!(at("refused", 500), None);
+        assert_eq!(
+            at("refused", 1000).as_deref(),
+            Some("refused (+2 similar suppressed)")
+        );
+        assert_eq!(at("refused", 2500).as_deref(), Some("refused"));
+
+        // by error, whatever else the message says
+        assert_eq!(at("status 500: a", 3000).as_deref(), Some("status 500: a"));
+        assert_eq!(at("status 500: b", 3010), None);
+    }
+
+    #[test]
+    fn counts_and_sorts_errors() {
+        let mut errors = ErrorCounts::default();
+        errors.record("status 500");
+        errors.record("timeout");
+
+        let mut other = ErrorCounts::default();
+        other.record("timeout");
+        errors.merge(other);
+
+        assert_eq!(errors.sorted(), vec![("timeout", 2), ("status 500", 1)]);
+        assert_eq!(errors.get(TIMEOUT), 2);
+        assert_eq!(errors.get("status 404"), 0);
+        assert_eq!(
+            errors.to_string(),
+            "top errors:\n\t2\ttimeout\n\t1\tstatus 500"
+        );
+    }
+
+    #[test]
+    fn caps_distinct_errors() {
+        let mut errors = ErrorCounts::default();
+        for status in 0..MAX_DISTINCT_ERRORS {
+            errors.record(format!("status {}", status));
+        }
+        errors.record("timeout");
+        errors.record("status 0");
+
+        let mut other = ErrorCounts::default();
+        other.record("connect error");
+        errors.merge(other);
+
+        assert_eq!(errors.get("status 0"), 2);
+        assert_eq!(errors.get(TIMEOUT), 0);
+        assert_eq!(errors.get(OTHER), 2);
+        assert_eq!(errors.total(), MAX_DISTINCT_ERRORS + 3);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::config::Method;
+use std::collections::HashMap;
+
+/// Request described by a `.http` file, in the format of the REST clients of
+/// VS Code and IntelliJ
+#[derive(Debug, PartialEq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    /// Headers in the `KEY:VALUE` format of `Config::header`
+    pub headers: Vec<String>,
+    pub body: Option<Vec<u8>>,
+}
+
+/// Error in a `.http` file, with the number of the line (starting at 1) where
+/// it was found
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl ParseError {
+    fn new(line: usize, reason: impl Into<String>) -> Self {
+        Self {
+            line,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Parse a `.http` file containing a single request: an optional method, the
+/// URL and an optional HTTP version on the first line, then the headers (one
+/// `Name: value` per line) and, after a blank line, the body.
+///
+/// Lines starting with `#` or `//` are comments, and lines such as
+/// `@host = example.com` before the request define variables. `{{name}}` is
+/// replaced by the variable `name`, or else by the result of `lookup` (e.g.
+/// an environment variable).
+pub fn parse(
+    source: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<HttpRequest, ParseError> {
+    let mut variables = HashMap::new();
+    let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+    let is_comment = |line: &str| {
+        let line = line.trim_start();
+        line.starts_with('#') || line.starts_with("//")
+    };
+
+    // variable definitions and comments before the request line
+    let (n, request_line) = loop {
+        let (n, line) = lines
+            .next()
+            .ok_or_else(|| ParseError::new(1, "no request found"))?;
+
+        if let Some(definition) = line.trim().strip_prefix('@') {
+            let (name, value) = definition
+                .split_once('=')
+                .ok_or_else(|| ParseError::new(n, "expected a variable @NAME = VALUE"))?;
+            let value = expand(value.trim(), n, &variables, &lookup)?;
+            variables.insert(name.trim().to_string(), value);
+        } else if !line.trim().is_empty() && !is_comment(line) {
+            break (n, expand(line, n, &variables, &lookup)?);
+        }
+    };
+
+    let mut parts = request_line.split_whitespace();
+    let (method, url) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(url), None, None, None) => (Method::Get, url),
+        (Some(method), Some(url), version, None)
+            if version.is_none_or(|v| v.starts_with("HTTP/")) =>
+        {
+            let method = method
+                .parse()
+                .map_err(|e| ParseError::new(n, format!("{}", e)))?;
+            (method, url)
+        }
+        _ => return Err(ParseError::new(n, "expected [METHOD] URL [HTTP-VERSION]")),
+    };
+
+    let mut headers = Vec::new();
+    let mut has_body = false;
+    for (n, line) in lines.by_ref() {
+        if line.trim().is_empty() {
+            has_body = true;
+            break;
+        }
+        if is_separator(line) {
+            break;
+        }
+        if is_comment(line) {
+            continue;
+        }
+
+        let line = expand(line, n, &variables, &lookup)?;
+        match line.split_once(':') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                headers.push(format!("{}:{}", name.trim(), value.trim()));
+            }
+            _ => return Err(ParseError::new(n, "expected a header NAME: VALUE")),
+        }
+    }
+
+    let mut body = Vec::new();
+    if has_body {
+        for (n, line) in lines.by_ref() {
+            if is_separator(line) {
+                break;
+            }
+            body.push(expand(line, n, &variables, &lookup)?);
+        }
+    }
+    while body.last().is_some_and(|line| line.trim().is_empty()) {
+        body.pop();
+    }
+
+    for (n, line) in lines {
+        if !line.trim().is_empty() && !is_comment(line) {
+            return Err(ParseError::new(n, "only one request per file is supported"));
+        }
+    }
+
+    Ok(HttpRequest {
+        method,
+        url: url.to_string(),
+        headers,
+        body: (!body.is_empty()).then(|| body.join("\n").into_bytes()),
+    })
+}
+
+/// Whether the line separates two requests (`###`, optionally followed by a
+/// name)
+fn is_separator(line: &str) -> bool {
+    line.trim_start().starts_with("###")
+}
+
+/// Replace the `{{name}}` variables in a line
+fn expand(
+    line: &str,
+    n: usize,
+    variables: &HashMap<String, String>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, ParseError> {
+    let mut expanded = String::new();
+    let mut rest = line;
+
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| ParseError::new(n, "unterminated variable"))?;
+        let name = rest[start + 2..start + end].trim();
+        let value = variables
+            .get(name)
+            .cloned()
+            .or_else(|| lookup(name))
+            .ok_or_else(|| ParseError::new(n, format!("undefined variable '{}'", name)))?;
+
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&value);
+        rest = &rest[start + end + 2..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(source: &str) -> Result<HttpRequest, ParseError> {
+        parse(source, |name| {
+            (name == "TOKEN").then(|| "secret".to_string())
+        })
+    }
+
+    #[test]
+    fn parses_minimal_request() {
+        let request = fixture(include_str!("../tests/fixtures/minimal.http")).unwrap();
+
+        assert_eq!(request.method, Method::Get);
+        assert_eq!(request.url, "http://localhost:8080/health");
+        assert!(request.headers.is_empty());
+        assert_eq!(request.body, None);
+    }
+
+    #[test]
+    fn keeps_colons_in_header_values() {
+        let request = fixture(include_str!("../tests/fixtures/headers.http")).unwrap();
+
+        assert_eq!(request.method, Method::Put);
+        assert_eq!(
+            request.headers,
+            vec![
+                "Content-Type:application/json",
+                "X-Time:12:30:00",
+                "Referer:http://example.com:8080/a",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_blank_lines_in_body() {
+        let request = fixture(include_str!("../tests/fixtures/body.http")).unwrap();
+
+        assert_eq!(request.method, Method::Post);
+        assert_eq!(request.headers, vec!["Content-Type:text/plain"]);
+        assert_eq!(
+            request.body.as_deref(),
+            Some(&b"first paragraph\n\nsecond paragraph"[..])
+        );
+    }
+
+    #[test]
+    fn expands_variables() {
+        let request = fixture(include_str!("../tests/fixtures/variables.http")).unwrap();
+
+        assert_eq!(request.url, "https://api.example.com/v1/users");
+        assert_eq!(request.headers, vec!["Authorization:Bearer secret"]);
+    }
+
+    #[test]
+    fn reports_line_of_errors() {
+        let error = |source| fixture(source).unwrap_err();
+
+        assert_eq!(error("").line, 1);
+        assert_eq!(error("# comment\nGET\tbad url here\n").line, 2);
+        assert_eq!(error("GET http://a\nNo colon\n").line, 2);
+        assert_eq!(error("GET http://a\n\n{{MISSING}}\n").line, 3);
+        assert_eq!(error("GET http://a/{{x\n").line, 1);
+        assert_eq!(error("GET http://a\n###\nGET http://b\n").line, 3);
+        assert_eq!(
+            error(include_str!("../tests/fixtures/invalid_method.http")),
+            ParseError::new(3, "invalid HTTP method 'G(ET'")
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use crate::window::Window;
+use hdrhistogram::Histogram;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Upper bounds of the buckets of the request duration histogram, in seconds
+const BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Metrics of a run in progress, shared with the connections
+pub(crate) struct Metrics {
+    pub passes: Arc<AtomicUsize>,
+    pub errors: Arc<AtomicUsize>,
+    /// Connections currently sending requests
+    pub active: Arc<AtomicUsize>,
+    /// Requests per second the test is paced at, if any
+    pub rate: Option<f64>,
+    /// Response times of the requests since the last scrape, in microseconds
+    pub times: Window,
+    /// Response times of all the requests until the last scrape
+    pub total_times: Mutex<Histogram<u64>>,
+}
+
+impl Metrics {
+    /// Metrics in the Prometheus text exposition format, with the given
+    /// response times
+    fn render(&self, times: &Histogram<u64>) -> String {
+        let mut text = String::new();
+
+        text.push_str("# HELP inquisitor_requests_total Requests completed.\n");
+        text.push_str("# TYPE inquisitor_requests_total counter\n");
+        for (result, count) in [("pass", &self.passes), ("error", &self.errors)] {
+            let count = count.load(Ordering::Relaxed);
+            writeln!(
+                text,
+                "inquisitor_requests_total{{result=\"{}\"}} {}",
+                result, count
+            )
+            .unwrap();
+        }
+
+        text.push_str("# HELP inquisitor_request_duration_seconds Response times.\n");
+        text.push_str("# TYPE inquisitor_request_duration_seconds histogram\n");
+        for le in BUCKETS {
+            let count = times.count_between(0, (le * 1_000_000.0) as u64);
+            writeln!(
+                text,
+                "inquisitor_request_duration_seconds_bucket{{le=\"{}\"}} {}",
+                le, count
+            )
+            .unwrap();
+        }
+        // the sum is only as precise as the histogram
+        let sum = times.mean() * times.len() as f64 / 1_000_000.0;
+        writeln!(
+            text,
+            "inquisitor_request_duration_seconds_bucket{{le=\"+Inf\"}} {}\n\
+             inquisitor_request_duration_seconds_sum {}\n\
+             inquisitor_request_duration_seconds_count {}",
+            times.len(),
+            sum,
+            times.len()
+        )
+        .unwrap();
+
+        text.push_str("# HELP inquisitor_active_connections Connections sending requests.\n");
+        text.push_str("# TYPE inquisitor_active_connections gauge\n");
+        let active = self.active.load(Ordering::Relaxed);
+        writeln!(text, "inquisitor_active_connections {}", active).unwrap();
+
+        if let Some(rate) = self.rate {
+            text.push_str("# HELP inquisitor_configured_rate Target requests per second.\n");
+            text.push_str("# TYPE inquisitor_configured_rate gauge\n");
+            writeln!(text, "inquisitor_configured_rate {}", rate).unwrap();
+        }
+
+        text
+    }
+}
+
+/// Answer requests for `/metrics` until the task is aborted
+pub(crate) async fn serve(listener: TcpListener, metrics: Arc<Metrics>) {
+    loop {
+        if let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(respond(stream, metrics.clone()));
+        }
+    }
+}
+
+async fn respond(mut stream: TcpStream, metrics: Arc<Metrics>) {
+    // only the request line is looked at
+    let mut request = [0; 1024];
+    let Ok(len) = stream.read(&mut request).await else {
+        return;
+    };
+    let request = String::from_utf8_lossy(&request[..len]);
+
+    let (status, body) = match request.split_whitespace().nth(1) {
+        Some("/metrics") => {
+            let mut times = metrics.total_times.lock().unwrap();
+            times.add(metrics.times.take()).expect("time out of bounds");
+            ("200 OK", metrics.render(&times))
+        }
+        _ => ("404 Not Found", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+
+    // the scraper may have gone away, which is not a problem for the test
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_exposition_format() {
+        let metrics = Metrics {
+            passes: Arc::new(AtomicUsize::new(3)),
+            errors: Arc::new(AtomicUsize::new(1)),
+            active: Arc::new(AtomicUsize::new(2)),
+            rate: Some(50.0),
+            times: Window::default(),
+            total_times: Mutex::new(crate::new_histogram()),
+        };
+        let mut times = crate::new_histogram();
+        for micros in [2_000, 20_000, 20_000, 3_000_000] {
+            times.record(micros).unwrap();
+        }
+
+        let text = metrics.render(&times);
+        for line in [
+            "inquisitor_requests_total{result=\"pass\"} 3",
+            "inquisitor_requests_total{result=\"error\"} 1",
+            "inquisitor_request_duration_seconds_bucket{le=\"0.001\"} 0",
+            "inquisitor_request_duration_seconds_bucket{le=\"0.005\"} 1",
+            "inquisitor_request_duration_seconds_bucket{le=\"0.025\"} 3",
+            "inquisitor_request_duration_seconds_bucket{le=\"2.5\"} 3",
+            "inquisitor_request_duration_seconds_bucket{le=\"5\"} 4",
+            "inquisitor_request_duration_seconds_bucket{le=\"+Inf\"} 4",
+            "inquisitor_request_duration_seconds_count 4",
+            "inquisitor_active_connections 2",
+            "inquisitor_configured_rate 50",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "{} not in:\n{}",
+                line,
+                text
+            );
+        }
+
+        let unpaced = Metrics {
+            rate: None,
+            ..metrics
+        };
+        assert!(!unpaced.render(&times).contains("configured_rate"));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::error::InquisitorError;
+use crate::stages::StageStats;
+use crate::time::Microseconds;
+
+/// Range of request ordinals (the position of a request among those sent by
+/// its connection, starting at 1)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OrdinalBucket {
+    pub first: usize,
+    /// Last ordinal of the bucket (included), or `None` if unbounded
+    pub last: Option<usize>,
+}
+
+impl OrdinalBucket {
+    pub fn contains(&self, ordinal: usize) -> bool {
+        ordinal >= self.first && self.last.is_none_or(|last| ordinal <= last)
+    }
+}
+
+impl std::fmt::Display for OrdinalBucket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self.last {
+            Some(last) if last == self.first => write!(f, "{}", last),
+            Some(last) => write!(f, "{}-{}", self.first, last),
+            None => write!(f, "{}+", self.first),
+        }
+    }
+}
+
+/// Ascending, non-overlapping ranges of request ordinals, reported separately
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrdinalBuckets(pub Vec<OrdinalBucket>);
+
+impl OrdinalBuckets {
+    /// Index of the bucket containing the given ordinal, if any
+    pub fn find(&self, ordinal: usize) -> Option<usize> {
+        self.0.iter().position(|bucket| bucket.contains(ordinal))
+    }
+}
+
+impl std::str::FromStr for OrdinalBuckets {
+    type Err = InquisitorError;
+
+    /// Parse a comma-separated list of ordinals (`N`), ranges (`N-M`) and
+    /// unbounded ranges (`N+`), e.g. `1,2-10,11-100,101+`
+    fn from_str(buckets: &str) -> Result<Self, Self::Err> {
+        let error = || InquisitorError::InvalidOrdinalBuckets(buckets.to_string());
+        let ordinal = |s: &str| s.trim().parse::<usize>().ok().filter(|n| *n > 0);
+        let mut parsed: Vec<OrdinalBucket> = Vec::new();
+
+        for bucket in buckets.split(',') {
+            let bucket = bucket.trim();
+            let (first, last) = if let Some(first) = bucket.strip_suffix('+') {
+                (ordinal(first), None)
+            } else if let Some((first, last)) = bucket.split_once('-') {
+                (ordinal(first), Some(ordinal(last).ok_or_else(error)?))
+            } else {
+                (ordinal(bucket), ordinal(bucket))
+            };
+            let first = first.ok_or_else(error)?;
+
+            // each bucket must start after the end of the previous one
+            let after_previous = match parsed.last() {
+                Some(previous) => previous.last.is_some_and(|end| first > end),
+                None => true,
+            };
+            if !after_previous || last.is_some_and(|last| last < first) {
+                return Err(error());
+            }
+
+            parsed.push(OrdinalBucket { first, last });
+        }
+
+        Ok(Self(parsed))
+    }
+}
+
+/// Results of the requests whose ordinals fall in a bucket
+#[derive(Debug)]
+pub struct OrdinalResults {
+    pub bucket: OrdinalBucket,
+    pub stats: StageStats,
+}
+
+impl OrdinalResults {
+    /// Bucket results as a JSON object, with times in microseconds
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "ordinals": self.bucket.to_string(),
+            "requests": self.stats.times.len(),
+            "errors": self.stats.errors,
+            "latencies_us": {
+                "50": self.stats.times.value_at_quantile(0.5),
+                "99": self.stats.times.value_at_quantile(0.99),
+            },
+        })
+    }
+}
+
+impl std::fmt::Display for OrdinalResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}\trequests {}\terrors {}\tp50 {}\tp99 {}",
+            self.bucket,
+            self.stats.times.len(),
+            self.stats.errors,
+            Microseconds(self.stats.times.value_at_quantile(0.5) as f64),
+            Microseconds(self.stats.times.value_at_quantile(0.99) as f64),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_buckets() {
+        let buckets: OrdinalBuckets = "1, 2-10,11-100,101+".parse().unwrap();
+        assert_eq!(
+            buckets.0,
+            vec![
+                OrdinalBucket {
+                    first: 1,
+                    last: Some(1)
+                },
+                OrdinalBucket {
+                    first: 2,
+                    last: Some(10)
+                },
+                OrdinalBucket {
+                    first: 11,
+                    last: Some(100)
+                },
+                OrdinalBucket {
+                    first: 101,
+                    last: None
+                },
+            ]
+        );
+        assert_eq!(buckets.0[1].to_string(), "2-10");
+        assert_eq!(buckets.0[3].to_string(), "101+");
+
+        assert!("0".parse::<OrdinalBuckets>().is_err());
+        assert!("1,1-5".parse::<OrdinalBuckets>().is_err());
+        assert!("5-2".parse::<OrdinalBuckets>().is_err());
+        assert!("10+,20".parse::<OrdinalBuckets>().is_err());
+        assert!("1,,2".parse::<OrdinalBuckets>().is_err());
+        assert!("first".parse::<OrdinalBuckets>().is_err());
+    }
+
+    #[test]
+    fn finds_bucket_of_ordinal() {
+        let buckets: OrdinalBuckets = "1,3-5,10+".parse().unwrap();
+
+        assert_eq!(buckets.find(1), Some(0));
+        assert_eq!(buckets.find(2), None);
+        assert_eq!(buckets.find(5), Some(1));
+        assert_eq!(buckets.find(9), None);
+        assert_eq!(buckets.find(1000), Some(2));
+    }
+}
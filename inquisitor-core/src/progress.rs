@@ -0,0 +1,87 @@
+use crate::time::Microseconds;
+use std::time::Duration;
+
+/// Interval at which the progress is printed with `--progress`, unless an
+/// interval is given
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Snapshot of a run in progress, printed as a single line
+pub struct Progress {
+    /// Time since the start of the test
+    pub elapsed: Duration,
+    /// Requests completed so far
+    pub requests: usize,
+    /// Requests completed per second over the last interval
+    pub rps: f64,
+    /// Requests failed so far
+    pub errors: usize,
+    /// p99 latency over the last interval, in microseconds, if any request
+    /// completed during it
+    pub p99: Option<f64>,
+    /// Part of the error budget consumed by the requests of the last
+    /// interval, in percent, if there is an SLO (`None` inside if no request
+    /// completed during it)
+    pub budget_burn: Option<Option<f64>>,
+}
+
+impl std::fmt::Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "[{:>7.1}s] requests: {}, rps: {:.1}, errors: {}, p99: ",
+            self.elapsed.as_secs_f64(),
+            self.requests,
+            self.rps,
+            self.errors
+        )?;
+        match self.p99 {
+            Some(p99) => write!(f, "{}", Microseconds(p99))?,
+            None => write!(f, "-")?,
+        }
+        match self.budget_burn {
+            Some(Some(burn)) => write!(f, ", budget burn: {:.1}%", burn),
+            Some(None) => write!(f, ", budget burn: -"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prints_one_line() {
+        let progress = Progress {
+            elapsed: Duration::from_secs(5),
+            requests: 123,
+            rps: 24.62,
+            errors: 2,
+            p99: Some(12_345.0),
+            budget_burn: None,
+        };
+        assert_eq!(
+            progress.to_string(),
+            "[    5.0s] requests: 123, rps: 24.6, errors: 2, p99: 12.3 ms"
+        );
+
+        let idle = Progress {
+            p99: None,
+            ..progress
+        };
+        assert!(idle.to_string().ends_with("p99: -"));
+
+        let with_slo = Progress {
+            budget_burn: Some(Some(250.0)),
+            ..idle
+        };
+        assert!(with_slo
+            .to_string()
+            .ends_with("p99: -, budget burn: 250.0%"));
+        let idle_with_slo = Progress {
+            budget_burn: Some(None),
+            ..with_slo
+        };
+        assert!(idle_with_slo.to_string().ends_with("budget burn: -"));
+    }
+}
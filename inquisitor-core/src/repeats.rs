@@ -0,0 +1,239 @@
+use crate::config::OutputFormat;
+use crate::results::{quantiles, TestResults, DEFAULT_PERCENTILES};
+use crate::time::Microseconds;
+use hdrhistogram::Histogram;
+
+/// Minimum, median, maximum, mean and standard deviation of a metric over
+/// several runs
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Spread {
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Sample standard deviation, 0 for a single run
+    pub stdev: f64,
+}
+
+impl Spread {
+    /// Spread of the given values, which must not be empty
+    pub fn of(values: impl IntoIterator<Item = f64>) -> Self {
+        let mut values: Vec<f64> = values.into_iter().collect();
+        values.sort_by(f64::total_cmp);
+
+        let middle = values.len() / 2;
+        let median = if values.len().is_multiple_of(2) {
+            (values[middle - 1] + values[middle]) / 2.0
+        } else {
+            values[middle]
+        };
+
+        let count = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / count;
+        let squares: f64 = values.iter().map(|value| (value - mean).powi(2)).sum();
+        let stdev = (squares / (count - 1.0).max(1.0)).sqrt();
+
+        Self {
+            min: values[0],
+            median,
+            max: values[values.len() - 1],
+            mean,
+            stdev,
+        }
+    }
+
+    fn to_json(self) -> serde_json::Value {
+        serde_json::json!({
+            "min": self.min,
+            "median": self.median,
+            "max": self.max,
+            "mean": self.mean,
+            "stdev": self.stdev,
+        })
+    }
+}
+
+/// Results of the same test run several times back-to-back
+#[derive(Debug)]
+pub struct RepeatedResults {
+    pub runs: Vec<TestResults>,
+    /// Response times of all the runs together, in microseconds
+    pub times: Histogram<u64>,
+}
+
+impl RepeatedResults {
+    pub fn new(runs: Vec<TestResults>) -> Self {
+        let mut times = crate::new_histogram();
+        for run in &runs {
+            times.add(&run.times).expect("time out of bounds");
+        }
+
+        Self { runs, times }
+    }
+
+    /// Spread of a metric over the runs
+    pub fn spread(&self, metric: impl Fn(&TestResults) -> f64) -> Spread {
+        Spread::of(self.runs.iter().map(metric))
+    }
+
+    /// Spread of the response time at the given quantile over the runs
+    pub fn percentile_spread(&self, quantile: f64) -> Spread {
+        self.spread(|run| run.percentile(quantile) as f64)
+    }
+
+    /// Labels and quantiles of the percentiles reported by the runs
+    fn quantiles(&self) -> Vec<(String, f64)> {
+        match self.runs.first() {
+            Some(run) => quantiles(&run.percentiles),
+            None => quantiles(&DEFAULT_PERCENTILES),
+        }
+    }
+
+    /// Results of all runs and their aggregate as a JSON object, with times
+    /// in microseconds
+    pub fn to_json(&self) -> serde_json::Value {
+        let quantiles = self.quantiles();
+        let spreads: serde_json::Map<_, _> = quantiles
+            .iter()
+            .map(|(label, q)| (label.clone(), self.percentile_spread(*q).to_json()))
+            .collect();
+        let combined: serde_json::Map<_, _> = quantiles
+            .iter()
+            .map(|(label, q)| (label.clone(), self.times.value_at_quantile(*q).into()))
+            .collect();
+
+        serde_json::json!({
+            "runs": self.runs.iter().map(TestResults::to_json).collect::<Vec<_>>(),
+            "aggregate": {
+                "runs": self.runs.len(),
+                "throughput": self.spread(TestResults::throughput).to_json(),
+                "error_rate": self.spread(TestResults::error_rate).to_json(),
+                "mean_us": self.spread(|run| run.times.mean()).to_json(),
+                "latencies_us": spreads,
+                "combined_latencies_us": combined,
+            },
+        })
+    }
+
+    /// Results of all runs as a CSV header row and a data row per run
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+
+        for (i, run) in self.runs.iter().enumerate() {
+            let run_csv = run.to_csv();
+            let (header, row) = run_csv.split_once('\n').unwrap_or_default();
+            if i == 0 {
+                csv.push_str(header);
+                csv.push('\n');
+            }
+            csv.push_str(row);
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
+
+impl std::fmt::Display for RepeatedResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        for (i, run) in self.runs.iter().enumerate() {
+            writeln!(
+                f,
+                "run {}:\t{} req./s\terrors {}/{}\tp50 {}\tp99 {}",
+                i + 1,
+                run.throughput() as usize,
+                run.errors,
+                run.iterations(),
+                Microseconds(run.percentile(0.5) as f64),
+                Microseconds(run.percentile(0.99) as f64),
+            )?;
+        }
+
+        let throughput = self.spread(TestResults::throughput);
+        let error_rate = self.spread(TestResults::error_rate);
+        let times = |spread: Spread| {
+            format!(
+                "{} / {} / {}\tmean {} ± {}",
+                Microseconds(spread.min),
+                Microseconds(spread.median),
+                Microseconds(spread.max),
+                Microseconds(spread.mean),
+                Microseconds(spread.stdev)
+            )
+        };
+
+        writeln!(f, "across {} runs (min / median / max):", self.runs.len())?;
+        writeln!(
+            f,
+            "\tthroughput\t{} / {} / {} req./s\tmean {:.0} ± {:.0} req./s",
+            throughput.min as usize,
+            throughput.median as usize,
+            throughput.max as usize,
+            throughput.mean,
+            throughput.stdev
+        )?;
+        writeln!(
+            f,
+            "\terror rate\t{:.2}% / {:.2}% / {:.2}%\tmean {:.2}% ± {:.2}%",
+            error_rate.min * 100.0,
+            error_rate.median * 100.0,
+            error_rate.max * 100.0,
+            error_rate.mean * 100.0,
+            error_rate.stdev * 100.0
+        )?;
+        writeln!(f, "\tmean\t{}", times(self.spread(|run| run.times.mean())))?;
+        let quantiles = self.quantiles();
+        for (label, q) in &quantiles {
+            writeln!(f, "\t{}%\t{}", label, times(self.percentile_spread(*q)))?;
+        }
+
+        writeln!(f, "combined latencies:")?;
+        for (label, q) in quantiles {
+            writeln!(
+                f,
+                "\t{}%\t{}",
+                label,
+                Microseconds(self.times.value_at_quantile(q) as f64)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Results of several runs as they are printed in the given format, ending
+/// with a newline
+pub fn format_repeated_results(results: &RepeatedResults, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => results.to_string(),
+        OutputFormat::Json => format!("{}\n", results.to_json()),
+        OutputFormat::Csv => results.to_csv(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_spread() {
+        assert_eq!(
+            Spread::of([3.0, 1.0, 2.0]),
+            Spread {
+                min: 1.0,
+                median: 2.0,
+                max: 3.0,
+                mean: 2.0,
+                stdev: 1.0,
+            }
+        );
+        assert_eq!(Spread::of([4.0, 1.0, 2.0, 10.0]).median, 3.0);
+        assert_eq!(Spread::of([5.0]).median, 5.0);
+        assert_eq!(Spread::of([5.0]).stdev, 0.0);
+        assert_eq!(
+            Spread::of([2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]).mean,
+            5.0
+        );
+        assert_eq!(Spread::of([1.0, 3.0]).stdev, 2f64.sqrt());
+    }
+}
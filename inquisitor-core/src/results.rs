@@ -0,0 +1,767 @@
+use crate::config::{OutputFormat, Pacing, StopReason};
+use crate::error::InquisitorError;
+use crate::failures::{self, ErrorCounts};
+use crate::ordinals::OrdinalResults;
+use crate::servers::ServerStats;
+use crate::stages::StageResults;
+use crate::statuses::StatusCounts;
+use crate::time::Microseconds;
+use crate::trend::Trend;
+use hdrhistogram::Histogram;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+/// Percentiles of the latencies reported by default
+pub const DEFAULT_PERCENTILES: [f64; 6] = [50.0, 75.0, 90.0, 95.0, 99.0, 99.9];
+
+/// Labels and quantiles of the given percentiles
+pub(crate) fn quantiles(percentiles: &[f64]) -> Vec<(String, f64)> {
+    percentiles
+        .iter()
+        .map(|p| (p.to_string(), quantile(*p)))
+        .collect()
+}
+
+/// Quantile of a percentile, rounded to 8 decimals so that e.g. 99.9 gives
+/// exactly 0.999
+pub(crate) fn quantile(percentile: f64) -> f64 {
+    (percentile * 1e6).round() / 1e8
+}
+
+/// Parse a percentile, which must be strictly between 0 and 100
+pub fn parse_percentile(percentile: &str) -> Result<f64, InquisitorError> {
+    match percentile.trim().parse::<f64>() {
+        Ok(p) if p > 0.0 && p < 100.0 => Ok(p),
+        _ => Err(InquisitorError::InvalidPercentile(percentile.to_string())),
+    }
+}
+
+/// Results of a load test
+#[derive(Debug)]
+pub struct TestResults {
+    /// Target URL of the test
+    pub url: String,
+    /// Number of connections used (the largest number of any stage)
+    pub connections: usize,
+    /// Response times in microseconds
+    pub times: Histogram<u64>,
+    /// Response times of the requests that succeeded, in microseconds
+    pub success_times: Histogram<u64>,
+    /// Response times of the requests that failed, in microseconds
+    pub error_times: Histogram<u64>,
+    /// Times until the responses started to arrive (their status and
+    /// headers), in microseconds, whereas the response times also include
+    /// the download of their bodies
+    pub ttfb_times: Histogram<u64>,
+    /// Response times of the first request of each connection, which
+    /// includes opening the connection (and the TLS handshake), in
+    /// microseconds
+    pub first_times: Histogram<u64>,
+    /// Response times of the other requests, sent on connections that are
+    /// already open (unless they were closed), in microseconds
+    pub warm_times: Histogram<u64>,
+    pub elapsed_us: f64,
+    /// Size of the response bodies received, as downloaded, or as announced
+    /// by `Content-Length` for those that are not (with `skip_body`)
+    pub bytes: u64,
+    /// Time spent holding at the end of the test, without sending requests,
+    /// after the iterations were reached but not yet the duration
+    pub hold: Option<Duration>,
+    pub errors: usize,
+    pub passes: usize,
+    pub stop_reason: Option<StopReason>,
+    /// Latency trend, if requested (`None` inside if there were not enough
+    /// samples)
+    pub trend: Option<Option<Trend>>,
+    /// Response times per server, if a server identifier header was given
+    pub servers: Option<ServerStats>,
+    /// How the requests were paced
+    pub pacing: Pacing,
+    /// Number of requests sent after their scheduled time, in open-loop mode
+    pub late: usize,
+    /// Number of requests still in flight at the end of the duration or of
+    /// the grace period after an interruption, which were cut off and
+    /// counted neither as passes nor as errors
+    pub cancelled: usize,
+    /// Seed of the random number generator, if one was used
+    pub seed: Option<u64>,
+    /// Time over which the connections were started, which is included in
+    /// the total time (and thus lowers the throughput)
+    pub ramp_up: Option<Duration>,
+    /// Results of each stage of the load profile, if any
+    pub stages: Vec<StageResults>,
+    /// Results of each bucket of request ordinals, if any
+    pub ordinals: Vec<OrdinalResults>,
+    /// Number of occurrences of each distinct error
+    pub failures: ErrorCounts,
+    /// Whether the errors are summarized in the text output (they are always
+    /// included in the JSON output)
+    pub print_failures: bool,
+    /// Number of responses with each status code, regardless of whether they
+    /// were counted as errors (e.g. a 200 whose body matched `failed_body`)
+    pub statuses: StatusCounts,
+    /// Number of requests sent again after a failure, which are not counted
+    /// as separate requests
+    pub retries: usize,
+    /// Whether a chart of the distribution of the response times is included
+    /// in the text output
+    pub print_chart: bool,
+    /// Target availability (in percent) used to compute the error budget
+    pub slo_availability: Option<f64>,
+    /// Trend of the error budget consumption over the course of the run, if
+    /// there is an SLO (`None` inside if there were not enough samples)
+    pub budget_trend: Option<Option<Trend>>,
+    /// Percentiles of the latencies reported
+    pub percentiles: Vec<f64>,
+    /// How server certificates were verified, for https targets
+    pub tls_posture: Option<String>,
+}
+
+impl TestResults {
+    /// Total number of requests sent
+    pub fn iterations(&self) -> usize {
+        self.passes + self.errors
+    }
+
+    /// Fraction of the requests that failed (0 if there were none)
+    pub fn error_rate(&self) -> f64 {
+        self.errors as f64 / self.iterations().max(1) as f64
+    }
+
+    /// Part of the error budget of the SLO consumed by the test, in percent,
+    /// if there is an SLO and there were requests
+    pub fn budget_consumed(&self) -> Option<f64> {
+        let slo_availability = self.slo_availability?;
+        (self.iterations() > 0).then(|| budget_consumed(self.error_rate(), slo_availability))
+    }
+
+    /// Number of requests that failed without a response (e.g. connection
+    /// errors and timeouts)
+    pub fn transport_errors(&self) -> usize {
+        self.statuses.transport_errors()
+    }
+
+    /// Number of requests that failed although a response was received
+    /// (e.g. with an unexpected status, or failing an assertion)
+    pub fn http_errors(&self) -> usize {
+        self.errors - self.transport_errors()
+    }
+
+    /// Number of requests that took longer than the timeout
+    pub fn timeouts(&self) -> usize {
+        self.failures.get(failures::TIMEOUT)
+    }
+
+    /// Number of requests whose connection could not be established
+    pub fn connect_errors(&self) -> usize {
+        self.failures.get(failures::CONNECT)
+    }
+
+    /// Bytes of response bodies received per second, while requests were
+    /// being sent
+    pub fn bytes_per_sec(&self) -> f64 {
+        self.bytes as f64 / (self.active_us() / 1_000_000.0)
+    }
+
+    /// Time during which requests were sent, in microseconds (the total
+    /// time minus the hold, if any)
+    pub fn active_us(&self) -> f64 {
+        let hold_us = self.hold.map_or(0.0, |hold| hold.as_micros() as f64);
+        (self.elapsed_us - hold_us).max(0.0)
+    }
+
+    /// Requests per second, while requests were being sent
+    pub fn throughput(&self) -> f64 {
+        self.iterations() as f64 / (self.active_us() / 1_000_000.0)
+    }
+
+    /// Response time (in microseconds) at the given quantile, e.g. 0.99 for
+    /// the 99th percentile
+    pub fn percentile(&self, quantile: f64) -> u64 {
+        self.times.value_at_quantile(quantile)
+    }
+
+    /// Summary of the results as a JSON object, with times in microseconds
+    pub fn to_json(&self) -> serde_json::Value {
+        let quantiles = quantiles(&self.percentiles);
+        let latencies = |times: &Histogram<u64>| {
+            quantiles
+                .iter()
+                .map(|(label, q)| (label.clone(), times.value_at_quantile(*q).into()))
+                .collect::<serde_json::Map<_, _>>()
+        };
+
+        serde_json::json!({
+            "total_time_us": self.elapsed_us,
+            "active_time_us": self.active_us(),
+            "hold_us": self.hold.map(|hold| hold.as_micros() as u64),
+            "stop_reason": self.stop_reason.map(|reason| reason.to_string()),
+            "pacing": self.pacing.to_string(),
+            "late": self.late,
+            "cancelled": self.cancelled,
+            "seed": self.seed,
+            "ramp_up_us": self.ramp_up.map(|ramp_up| ramp_up.as_micros() as u64),
+            "stages": self.stages.iter().map(StageResults::to_json).collect::<Vec<_>>(),
+            "ordinals": self.ordinals.iter().map(OrdinalResults::to_json).collect::<Vec<_>>(),
+            "passes": self.passes,
+            "errors": self.errors,
+            "error_rate": self.error_rate(),
+            "slo_availability": self.slo_availability,
+            "error_budget_consumed_pct": self.budget_consumed(),
+            "error_budget_trend": self.budget_trend.flatten().map(|trend| trend.to_json()),
+            "http_errors": self.http_errors(),
+            "transport_errors": self.transport_errors(),
+            "timeouts": self.timeouts(),
+            "connect_errors": self.connect_errors(),
+            "retries": self.retries,
+            "throughput": self.throughput(),
+            "bytes_received": self.bytes,
+            "bytes_per_sec": self.bytes_per_sec(),
+            "response_times_us": {
+                "mean": self.times.mean(),
+                "stdev": self.times.stdev(),
+                "min": self.times.min(),
+                "max": self.times.max(),
+            },
+            "latencies_us": latencies(&self.times),
+            "success_latencies_us": latencies(&self.success_times),
+            "error_latencies_us": latencies(&self.error_times),
+            "ttfb_us": latencies(&self.ttfb_times),
+            "first_request_latencies_us": latencies(&self.first_times),
+            "warm_latencies_us": latencies(&self.warm_times),
+            "errors_by_kind": self.failures.to_json(),
+            "status_codes": self.statuses.to_json(),
+            "servers": self.servers.as_ref().map(ServerStats::to_json),
+        })
+    }
+
+    /// Summary of the results as a CSV header row and a data row, with raw
+    /// times in microseconds
+    pub fn to_csv(&self) -> String {
+        let mut header = vec![
+            "url",
+            "connections",
+            "duration_us",
+            "passes",
+            "errors",
+            "rps",
+            "mean_us",
+            "stdev_us",
+            "min_us",
+            "max_us",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect::<Vec<_>>();
+        let mut row = vec![
+            csv_field(&self.url),
+            self.connections.to_string(),
+            (self.elapsed_us as u64).to_string(),
+            self.passes.to_string(),
+            self.errors.to_string(),
+            self.throughput().to_string(),
+            self.times.mean().to_string(),
+            self.times.stdev().to_string(),
+            self.times.min().to_string(),
+            self.times.max().to_string(),
+        ];
+
+        for (label, q) in quantiles(&self.percentiles) {
+            header.push(format!("p{}", label.replace('.', "")));
+            row.push(self.times.value_at_quantile(q).to_string());
+        }
+
+        format!("{}\n{}", header.join(","), row.join(","))
+    }
+
+    /// Full distribution of the response times in the `.hgrm` format of
+    /// HdrHistogram
+    pub fn to_hgrm(&self) -> String {
+        hgrm(&self.times)
+    }
+
+    /// Every distinct response time with its count, as CSV (see
+    /// `write_histogram_csv`)
+    pub fn to_histogram_csv(&self) -> String {
+        histogram_csv(&self.times)
+    }
+}
+
+/// Full distribution of response times (in microseconds) in the percentile
+/// distribution format of HdrHistogram (`.hgrm`), with values in
+/// milliseconds, as read by its plotting tools
+pub fn hgrm(times: &Histogram<u64>) -> String {
+    // microseconds per unit of the values
+    const SCALE: f64 = 1000.0;
+    let mut hgrm = format!(
+        "{:>12} {:>14} {:>10} {:>14}\n\n",
+        "Value", "Percentile", "TotalCount", "1/(1-Percentile)"
+    );
+
+    let mut total = 0;
+    for value in times.iter_quantiles(5) {
+        total += value.count_since_last_iteration();
+        let quantile = value.quantile_iterated_to();
+        let ms = value.value_iterated_to() as f64 / SCALE;
+
+        if quantile < 1.0 {
+            let _ = writeln!(
+                hgrm,
+                "{:12.3} {:2.12} {:10} {:14.2}",
+                ms,
+                quantile,
+                total,
+                1.0 / (1.0 - quantile)
+            );
+        } else {
+            let _ = writeln!(hgrm, "{:12.3} {:2.12} {:10}", ms, quantile, total);
+        }
+    }
+
+    let sub_buckets = (2 * 10u32.pow(times.sigfig() as u32)).next_power_of_two();
+    let _ = write!(
+        hgrm,
+        "#[Mean    = {:12.3}, StdDeviation   = {:12.3}]\n\
+         #[Max     = {:12.3}, Total count    = {:12}]\n\
+         #[Buckets = {:12}, SubBuckets     = {:12}]\n",
+        times.mean() / SCALE,
+        times.stdev() / SCALE,
+        times.max() as f64 / SCALE,
+        times.len(),
+        times.buckets(),
+        sub_buckets,
+    );
+
+    hgrm
+}
+
+/// Write every distinct response time (in microseconds) recorded in the
+/// histogram with its count, as CSV with a `value_us,count` header. Values
+/// are the highest equivalent value of their bucket, so a run can be
+/// recorded again (or merged) from the file without losing precision.
+pub fn write_histogram_csv<W: std::io::Write>(
+    times: &Histogram<u64>,
+    mut writer: W,
+) -> std::io::Result<()> {
+    writeln!(writer, "value_us,count")?;
+    for value in times.iter_recorded() {
+        writeln!(
+            writer,
+            "{},{}",
+            value.value_iterated_to(),
+            value.count_at_value()
+        )?;
+    }
+
+    writer.flush()
+}
+
+/// Every distinct response time with its count, as CSV (see
+/// `write_histogram_csv`)
+pub fn histogram_csv(times: &Histogram<u64>) -> String {
+    let mut csv = Vec::new();
+    // writing to a `Vec` cannot fail
+    let _ = write_histogram_csv(times, &mut csv);
+    String::from_utf8(csv).expect("the CSV is ASCII")
+}
+
+/// Represents an amount of bytes, displayed with decimal units
+pub struct Bytes(pub f64);
+
+impl std::fmt::Display for Bytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self.0 {
+            x if x < 1_000.0 => write!(f, "{:.0} B", x),
+            x if x < 1_000_000.0 => write!(f, "{:.2} kB", x / 1_000.0),
+            x if x < 1_000_000_000.0 => write!(f, "{:.2} MB", x / 1_000_000.0),
+            x => write!(f, "{:.2} GB", x / 1_000_000_000.0),
+        }
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Print the results to stdout in the given format
+pub fn print_results(results: &TestResults, format: OutputFormat) {
+    print!("{}", format_results(results, format));
+}
+
+/// Results as they are printed in the given format, ending with a newline
+pub fn format_results(results: &TestResults, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => {
+            let mut text = String::new();
+            write_text(&mut text, results).expect("writing to a string cannot fail");
+            text
+        }
+        OutputFormat::Json => format!("{}\n", results.to_json()),
+        OutputFormat::Csv => format!("{}\n", results.to_csv()),
+    }
+}
+
+/// Part of the error budget of an SLO with the given availability (in
+/// percent) consumed by requests failing at the given rate, in percent (over
+/// 100 when more requests failed than the SLO allows)
+pub fn budget_consumed(error_rate: f64, slo_availability: f64) -> f64 {
+    // fraction of the requests allowed to fail by the SLO
+    let budget = 1.0 - slo_availability / 100.0;
+    100.0 * error_rate / budget
+}
+
+/// File the results are written to at the end of the test. It is created
+/// beforehand, so that an unwritable path is reported before running, and
+/// replaced atomically. The temporary file is removed if it is dropped
+/// without being written.
+pub struct ResultsFile {
+    path: String,
+    /// File the results are written to before being renamed to `path`
+    temp_path: String,
+}
+
+impl ResultsFile {
+    /// Check that the results can be written to `path`, by creating the
+    /// temporary file next to it
+    pub fn create(path: &str) -> Result<Self, InquisitorError> {
+        let temp_path = format!("{}.tmp", path);
+        std::fs::File::create(&temp_path)
+            .map_err(|e| InquisitorError::OutputFile(path.to_string(), e))?;
+
+        Ok(Self {
+            path: path.to_string(),
+            temp_path,
+        })
+    }
+
+    /// Write the results and move them to the final path
+    pub fn write(self, contents: &str) -> Result<(), InquisitorError> {
+        std::fs::write(&self.temp_path, contents)
+            .and_then(|_| std::fs::rename(&self.temp_path, &self.path))
+            .map_err(|e| InquisitorError::OutputFile(self.path.clone(), e))
+    }
+}
+
+impl Drop for ResultsFile {
+    fn drop(&mut self) {
+        // nothing is left once it was renamed
+        let _ = std::fs::remove_file(&self.temp_path);
+    }
+}
+
+fn write_text(f: &mut String, results: &TestResults) -> std::fmt::Result {
+    let TestResults {
+        times,
+        success_times,
+        error_times,
+        ttfb_times,
+        first_times,
+        warm_times,
+        elapsed_us,
+        bytes,
+        hold,
+        errors,
+        stop_reason,
+        trend,
+        servers,
+        pacing,
+        late,
+        cancelled,
+        seed,
+        ramp_up,
+        stages,
+        ordinals,
+        failures,
+        print_failures,
+        statuses,
+        print_chart,
+        slo_availability,
+        tls_posture,
+        percentiles,
+        budget_trend,
+        ..
+    } = results;
+    let iterations = results.iterations();
+    let rps = results.throughput() as usize;
+
+    if let Some(tls_posture) = tls_posture {
+        writeln!(f, "tls: {}", tls_posture)?;
+    }
+    writeln!(f, "total time: {}", Microseconds(*elapsed_us))?;
+    if hold.is_some() {
+        writeln!(
+            f,
+            "active time: {} (then held until the end of the duration)",
+            Microseconds(results.active_us())
+        )?;
+    }
+    if let Some(reason) = stop_reason {
+        writeln!(f, "stopped by: {}", reason)?;
+    }
+    write!(f, "errors: {}/{}", errors, iterations)?;
+
+    if *errors > 0 {
+        writeln!(f, " ({:.2}%)", results.error_rate() * 100.0)?;
+        writeln!(f, "http errors: {}", results.http_errors())?;
+        writeln!(f, "transport errors: {}", results.transport_errors())?;
+    } else {
+        writeln!(f)?;
+    }
+    if results.timeouts() > 0 {
+        writeln!(f, "timeouts: {}", results.timeouts())?;
+    }
+    if results.connect_errors() > 0 {
+        writeln!(f, "connect errors: {}", results.connect_errors())?;
+    }
+    if results.retries > 0 {
+        writeln!(f, "retries: {}", results.retries)?;
+    }
+    if *cancelled > 0 {
+        writeln!(f, "cancelled in flight: {}", cancelled)?;
+    }
+    writeln!(f, "{}", statuses)?;
+
+    match (slo_availability, results.budget_consumed()) {
+        (Some(slo), Some(consumed)) => writeln!(
+            f,
+            "error budget: {:.1}% consumed for {}% availability",
+            consumed, slo
+        )?,
+        (Some(slo), None) => writeln!(
+            f,
+            "error budget: n/a (no requests) for {}% availability",
+            slo
+        )?,
+        _ => {}
+    }
+    match budget_trend {
+        Some(Some(trend)) => writeln!(f, "error budget trend: {}", trend)?,
+        Some(None) => writeln!(f, "error budget trend: not enough samples")?,
+        None => {}
+    }
+    writeln!(f, "throughput: {} req./s", rps,)?;
+    writeln!(
+        f,
+        "data: {} received ({}/s)",
+        Bytes(*bytes as f64),
+        Bytes(results.bytes_per_sec())
+    )?;
+    if let Some(ramp_up) = ramp_up {
+        writeln!(
+            f,
+            "ramp-up: {} (included in the total time and throughput)",
+            Microseconds(ramp_up.as_micros() as f64)
+        )?;
+    }
+    if *pacing != Pacing::Unlimited {
+        writeln!(f, "pacing: {}", pacing)?;
+    }
+    if let Pacing::ArrivalRate(..) = pacing {
+        writeln!(f, "late requests: {}/{}", late, iterations)?;
+    }
+    if let Some(seed) = seed {
+        writeln!(f, "seed: {}", seed)?;
+    }
+
+    writeln!(
+        f,
+        "response times:\n\tmean\t{}\n\tst.dev\t{}\n\tmin\t{}\n\tmax\t{}",
+        Microseconds(times.mean()),
+        Microseconds(times.stdev()),
+        Microseconds(times.min() as f64),
+        Microseconds(times.max() as f64),
+    )?;
+
+    let quantiles = quantiles(percentiles);
+    write_latencies(f, "latencies", times, &quantiles)?;
+    write_latencies(f, "time to first byte", ttfb_times, &quantiles)?;
+    if !first_times.is_empty() && !warm_times.is_empty() {
+        writeln!(
+            f,
+            "first request of each connection: mean {}, warm requests: mean {}",
+            Microseconds(first_times.mean()),
+            Microseconds(warm_times.mean())
+        )?;
+    }
+    if results.passes > 0 {
+        let title = "latencies of successful requests";
+        write_latencies(f, title, success_times, &quantiles)?;
+    }
+    if *errors > 0 {
+        write_latencies(f, "latencies of failed requests", error_times, &quantiles)?;
+    }
+
+    if !stages.is_empty() {
+        writeln!(f, "stages:")?;
+        for stage in stages {
+            writeln!(f, "\t{}", stage)?;
+        }
+    }
+
+    if !ordinals.is_empty() {
+        writeln!(f, "request ordinals:")?;
+        for bucket in ordinals {
+            writeln!(f, "\t{}", bucket)?;
+        }
+    }
+
+    match trend {
+        Some(Some(trend)) => writeln!(f, "latency trend: {}", trend)?,
+        Some(None) => writeln!(f, "latency trend: not enough samples")?,
+        None => {}
+    }
+
+    if let Some(servers) = servers {
+        writeln!(f, "{}", servers)?;
+    }
+
+    if *print_failures && !failures.is_empty() {
+        writeln!(f, "{}", failures)?;
+    }
+
+    if *print_chart {
+        write_chart(f, times)?;
+    }
+
+    Ok(())
+}
+
+/// Write the response times at the given quantiles, under a title
+fn write_latencies(
+    f: &mut String,
+    title: &str,
+    times: &Histogram<u64>,
+    quantiles: &[(String, f64)],
+) -> std::fmt::Result {
+    writeln!(f, "{}:", title)?;
+    for (label, q) in quantiles {
+        writeln!(
+            f,
+            "\t{}%\t{}",
+            label,
+            Microseconds(times.value_at_quantile(*q) as f64)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Number of buckets of the latency chart
+const CHART_BUCKETS: usize = 20;
+
+/// Horizontal bar chart of the number of response times in log-spaced
+/// buckets, as wide as the terminal (per `COLUMNS`, or 80 columns)
+fn write_chart(f: &mut String, times: &Histogram<u64>) -> std::fmt::Result {
+    writeln!(f, "latency distribution:")?;
+    if times.is_empty() {
+        return writeln!(f, "\tno samples");
+    }
+
+    let min = times.min().max(1) as f64;
+    let max = times.max().max(1) as f64;
+    // all samples in a single bucket if they are (about) the same
+    let buckets = if max > min { CHART_BUCKETS } else { 1 };
+    let bucket_of = |value: u64| {
+        let position = (value.max(1) as f64 / min).ln() / (max / min).ln();
+        ((position * buckets as f64) as usize).min(buckets - 1)
+    };
+    let bound = |i: usize| min * (max / min).powf(i as f64 / buckets as f64);
+
+    let mut counts = vec![0; buckets];
+    for value in times.iter_recorded() {
+        let bucket = if buckets == 1 {
+            0
+        } else {
+            bucket_of(value.value_iterated_to())
+        };
+        counts[bucket] += value.count_at_value();
+    }
+
+    let columns = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse::<usize>().ok())
+        .unwrap_or(80);
+    // room left by the tab, the bucket range and the count
+    let width = columns.saturating_sub(40).max(10);
+    let largest = counts.iter().copied().max().unwrap_or(1).max(1);
+
+    for (i, count) in counts.into_iter().enumerate() {
+        let bar = (count as usize * width).div_ceil(largest as usize);
+        writeln!(
+            f,
+            "\t{:>9} - {:<9}\t{} {}",
+            Microseconds(bound(i)).to_string(),
+            Microseconds(bound(i + 1)).to_string(),
+            "#".repeat(bar),
+            count
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_percentiles() {
+        assert_eq!(parse_percentile("99.99").unwrap(), 99.99);
+        assert_eq!(parse_percentile(" 50").unwrap(), 50.0);
+        for invalid in ["0", "100", "-1", "101", "abc", "", "NaN", "99%"] {
+            assert!(parse_percentile(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn writes_histogram_as_csv() {
+        let mut times = crate::new_histogram();
+        for micros in [150, 150, 2_000, 2_001, 3_000_000] {
+            times.record(micros).unwrap();
+        }
+
+        let mut csv = Vec::new();
+        write_histogram_csv(&times, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("value_us,count"));
+
+        let rows: Vec<(u64, u64)> = lines
+            .map(|line| {
+                let (value, count) = line.split_once(',').unwrap();
+                (value.parse().unwrap(), count.parse().unwrap())
+            })
+            .collect();
+        assert_eq!(rows.iter().map(|(_, count)| count).sum::<u64>(), 5);
+        assert_eq!(rows[0], (150, 2));
+        assert!(rows.windows(2).all(|w| w[0].0 < w[1].0));
+
+        let mut merged = crate::new_histogram();
+        for (value, count) in rows {
+            merged.record_n(value, count).unwrap();
+        }
+        assert_eq!(merged, times);
+    }
+
+    #[test]
+    fn labels_quantiles() {
+        assert_eq!(
+            quantiles(&DEFAULT_PERCENTILES),
+            [
+                ("50", 0.5),
+                ("75", 0.75),
+                ("90", 0.9),
+                ("95", 0.95),
+                ("99", 0.99),
+                ("99.9", 0.999),
+            ]
+            .map(|(label, q)| (label.to_string(), q))
+        );
+        assert_eq!(quantiles(&[99.99]), [("99.99".to_string(), 0.9999)]);
+    }
+}
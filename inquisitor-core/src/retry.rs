@@ -0,0 +1,98 @@
+use crate::error::InquisitorError;
+use crate::statuses::StatusSet;
+
+/// Failures after which a request is sent again
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RetryOn {
+    /// A response with this status code
+    Status(u16),
+    /// A response with a status code in this class (e.g. `5` for 5xx)
+    StatusClass(u16),
+    /// No response at all (e.g. connection errors and timeouts)
+    Transport,
+}
+
+impl std::str::FromStr for RetryOn {
+    type Err = InquisitorError;
+
+    /// Parse a status code (`429`), a class of status codes (`5xx`) or
+    /// `io-error`
+    fn from_str(condition: &str) -> Result<Self, Self::Err> {
+        let error = || InquisitorError::InvalidRetryOn(condition.to_string());
+        let condition = condition.trim().to_lowercase();
+
+        if condition == "io-error" {
+            return Ok(Self::Transport);
+        }
+        if let Some(class) = condition.strip_suffix("xx") {
+            return match class.parse() {
+                Ok(class @ 1..=5) => Ok(Self::StatusClass(class)),
+                _ => Err(error()),
+            };
+        }
+        match condition.parse() {
+            Ok(status @ 100..=599) => Ok(Self::Status(status)),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl std::fmt::Display for RetryOn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::Status(status) => write!(f, "{}", status),
+            Self::StatusClass(class) => write!(f, "{}xx", class),
+            Self::Transport => write!(f, "io-error"),
+        }
+    }
+}
+
+/// Whether a request should be sent again after getting the given status
+/// code (or no response at all, for `None`), if it is not one of the
+/// expected statuses. Without conditions, every failure is retried.
+pub fn should_retry(conditions: &[RetryOn], expected: &StatusSet, status: Option<u16>) -> bool {
+    match status {
+        Some(status) if expected.contains(status) => false,
+        _ if conditions.is_empty() => true,
+        Some(status) => conditions.iter().any(|condition| match condition {
+            RetryOn::Status(retried) => status == *retried,
+            RetryOn::StatusClass(class) => status / 100 == *class,
+            RetryOn::Transport => false,
+        }),
+        None => conditions.contains(&RetryOn::Transport),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_conditions() {
+        assert_eq!("5xx".parse::<RetryOn>().unwrap(), RetryOn::StatusClass(5));
+        assert_eq!("429".parse::<RetryOn>().unwrap(), RetryOn::Status(429));
+        assert_eq!("IO-Error".parse::<RetryOn>().unwrap(), RetryOn::Transport);
+        assert!("6xx".parse::<RetryOn>().is_err());
+        assert!("42".parse::<RetryOn>().is_err());
+        assert!("timeout".parse::<RetryOn>().is_err());
+    }
+
+    #[test]
+    fn retries_matching_failures() {
+        let expected = StatusSet::default();
+        assert!(should_retry(&[], &expected, Some(500)));
+        assert!(should_retry(&[], &expected, None));
+        assert!(!should_retry(&[], &expected, Some(204)));
+
+        let conditions = [RetryOn::StatusClass(5), RetryOn::Status(429)];
+        assert!(should_retry(&conditions, &expected, Some(503)));
+        assert!(should_retry(&conditions, &expected, Some(429)));
+        assert!(!should_retry(&conditions, &expected, Some(404)));
+        assert!(!should_retry(&conditions, &expected, None));
+        assert!(should_retry(&[RetryOn::Transport], &expected, None));
+
+        let expected: StatusSet = "503".parse().unwrap();
+        assert!(!should_retry(&[], &expected, Some(503)));
+        assert!(should_retry(&[], &expected, Some(200)));
+    }
+}
@@ -0,0 +1,133 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Delay after its scheduled time from which a request is considered late
+pub const LATE_THRESHOLD: Duration = Duration::from_millis(10);
+
+/// Fixed timeline of request start times, independent of the responses
+pub struct Schedule(Timeline);
+
+enum Timeline {
+    /// Start times evenly spaced by `period`
+    Constant {
+        start: Instant,
+        period: Duration,
+        next: AtomicU64,
+    },
+    /// Start times separated by random gaps, drawn one at a time after the
+    /// next start time
+    Poisson(Box<Mutex<(Instant, ExponentialGaps)>>),
+}
+
+impl Schedule {
+    /// Schedule `rate` requests per second at constant intervals, starting
+    /// at `start`
+    pub fn new(start: Instant, rate: f64) -> Self {
+        Self(Timeline::Constant {
+            start,
+            period: Duration::from_secs_f64(1.0 / rate),
+            next: AtomicU64::new(0),
+        })
+    }
+
+    /// Schedule an average of `rate` requests per second as a Poisson
+    /// process, starting at `start`. The same seed gives the same timeline.
+    pub fn poisson(start: Instant, rate: f64, seed: u64) -> Self {
+        Self(Timeline::Poisson(Box::new(Mutex::new((
+            start,
+            ExponentialGaps::new(rate, seed),
+        )))))
+    }
+
+    /// Claim the next start time of the timeline
+    pub fn next(&self) -> Instant {
+        match &self.0 {
+            Timeline::Constant {
+                start,
+                period,
+                next,
+            } => {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                *start + period.mul_f64(i as f64)
+            }
+            Timeline::Poisson(state) => {
+                let (next, gaps) = &mut *state.lock().unwrap();
+                let scheduled_time = *next;
+                *next += gaps.next_gap();
+                scheduled_time
+            }
+        }
+    }
+}
+
+/// Exponentially distributed gaps between the arrivals of a Poisson process
+pub struct ExponentialGaps {
+    rng: StdRng,
+    rate: f64,
+}
+
+impl ExponentialGaps {
+    /// Gaps for an average of `rate` arrivals per second
+    pub fn new(rate: f64, seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            rate,
+        }
+    }
+
+    /// Draw the gap until the next arrival
+    pub fn next_gap(&mut self) -> Duration {
+        // inverse transform sampling, `1 - u` is in (0, 1] so the logarithm
+        // is finite
+        let u: f64 = self.rng.gen();
+        Duration::from_secs_f64(-(1.0 - u).ln() / self.rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_times_are_evenly_spaced() {
+        let start = Instant::now();
+        let schedule = Schedule::new(start, 4.0);
+
+        assert_eq!(schedule.next(), start);
+        assert_eq!(schedule.next(), start + Duration::from_millis(250));
+        assert_eq!(schedule.next(), start + Duration::from_millis(500));
+    }
+
+    #[test]
+    fn gaps_are_exponentially_distributed() {
+        let rate = 50.0;
+        let n = 100_000;
+        let mut gaps = ExponentialGaps::new(rate, 42);
+        let gaps: Vec<f64> = (0..n).map(|_| gaps.next_gap().as_secs_f64()).collect();
+
+        let mean = gaps.iter().sum::<f64>() / n as f64;
+        let var = gaps.iter().map(|g| (g - mean).powi(2)).sum::<f64>() / n as f64;
+        // P(gap > t) = exp(-rate * t)
+        let above_mean = gaps.iter().filter(|g| **g > 1.0 / rate).count() as f64 / n as f64;
+
+        assert!((mean * rate - 1.0).abs() < 0.02);
+        assert!((var.sqrt() * rate - 1.0).abs() < 0.02);
+        assert!((above_mean - (-1.0f64).exp()).abs() < 0.01);
+    }
+
+    #[test]
+    fn poisson_schedule_is_reproducible() {
+        let start = Instant::now();
+        let a = Schedule::poisson(start, 10.0, 7);
+        let b = Schedule::poisson(start, 10.0, 7);
+
+        assert_eq!(a.next(), start);
+        assert_eq!(b.next(), start);
+        for _ in 0..10 {
+            assert_eq!(a.next(), b.next());
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::time::Microseconds;
+use hdrhistogram::Histogram;
+use std::collections::HashMap;
+
+/// Maximum number of distinct server identifiers tracked, further ones are
+/// grouped together
+pub const MAX_SERVER_IDS: usize = 64;
+
+/// Bucket for responses without the server identifier header
+pub const UNKNOWN_SERVER: &str = "unknown";
+
+/// Bucket for server identifiers seen after `MAX_SERVER_IDS` was reached
+pub const OTHER_SERVERS: &str = "other";
+
+/// Response times per server instance, as identified by a response header
+#[derive(Debug, Default)]
+pub struct ServerStats {
+    times: HashMap<String, Histogram<u64>>,
+}
+
+impl ServerStats {
+    /// Record a response time (in microseconds) for the given server
+    pub fn record(&mut self, server_id: Option<&str>, elapsed: u64) {
+        self.bucket(server_id.unwrap_or(UNKNOWN_SERVER))
+            .record(elapsed)
+            .expect("time out of bounds");
+    }
+
+    /// Add the response times recorded in `other` to these
+    pub fn merge(&mut self, other: ServerStats) {
+        for (server_id, times) in other.times {
+            self.bucket(&server_id)
+                .add(times)
+                .expect("time out of bounds");
+        }
+    }
+
+    fn bucket(&mut self, server_id: &str) -> &mut Histogram<u64> {
+        // the buckets that are not servers don't count toward the limit
+        let server_id = if self.times.contains_key(server_id)
+            || self.count() < MAX_SERVER_IDS
+            || server_id == UNKNOWN_SERVER
+        {
+            server_id
+        } else {
+            OTHER_SERVERS
+        };
+
+        self.times
+            .entry(server_id.to_string())
+            .or_insert_with(crate::new_histogram)
+    }
+
+    /// Number of identified servers (i.e. not counting the responses without
+    /// identifier)
+    pub fn count(&self) -> usize {
+        self.times
+            .keys()
+            .filter(|id| *id != UNKNOWN_SERVER && *id != OTHER_SERVERS)
+            .count()
+    }
+
+    /// Number of responses and latencies of each server as a JSON object,
+    /// with times in microseconds
+    pub fn to_json(&self) -> serde_json::Value {
+        self.times
+            .iter()
+            .map(|(id, times)| {
+                let server = serde_json::json!({
+                    "responses": times.len(),
+                    "latencies_us": {
+                        "50": times.value_at_quantile(0.5),
+                        "99": times.value_at_quantile(0.99),
+                    },
+                });
+                (id.clone(), server)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+impl std::fmt::Display for ServerStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let p99s = self
+            .times
+            .iter()
+            .filter(|(id, _)| *id != UNKNOWN_SERVER && *id != OTHER_SERVERS)
+            .map(|(_, times)| times.value_at_quantile(0.99));
+
+        write!(f, "servers: {}", self.count())?;
+        if let (Some(min), Some(max)) = (p99s.clone().min(), p99s.max()) {
+            if min > 0 {
+                write!(
+                    f,
+                    " (slowest p99 is {:.1}x the fastest)",
+                    max as f64 / min as f64
+                )?;
+            }
+        }
+
+        let mut servers: Vec<_> = self.times.iter().collect();
+        servers.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+
+        for (id, times) in servers {
+            write!(
+                f,
+                "\n\t{}\t{} responses\tp50 {}\tp99 {}",
+                id,
+                times.len(),
+                Microseconds(times.value_at_quantile(0.5) as f64),
+                Microseconds(times.value_at_quantile(0.99) as f64),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_servers() {
+        let mut stats = ServerStats::default();
+        stats.record(Some("a"), 100);
+        stats.record(Some("a"), 100);
+        stats.record(None, 100);
+
+        let mut other = ServerStats::default();
+        other.record(Some("b"), 300);
+        stats.merge(other);
+
+        assert_eq!(stats.count(), 2);
+        assert_eq!(stats.times["a"].len(), 2);
+        assert_eq!(stats.times[UNKNOWN_SERVER].len(), 1);
+        assert!(stats
+            .to_string()
+            .starts_with("servers: 2 (slowest p99 is 3.0x the fastest)"));
+    }
+
+    #[test]
+    fn caps_cardinality() {
+        let mut stats = ServerStats::default();
+        stats.record(None, 100);
+        for i in 0..MAX_SERVER_IDS + 10 {
+            stats.record(Some(&i.to_string()), 100);
+        }
+
+        assert_eq!(stats.count(), MAX_SERVER_IDS);
+        assert_eq!(stats.times[OTHER_SERVERS].len(), 10);
+        assert_eq!(stats.times[UNKNOWN_SERVER].len(), 1);
+
+        let mut other = ServerStats::default();
+        other.record(Some("new"), 100);
+        stats.merge(other);
+        assert_eq!(stats.count(), MAX_SERVER_IDS);
+        assert_eq!(stats.times[OTHER_SERVERS].len(), 11);
+    }
+
+    #[test]
+    fn converts_to_json() {
+        let mut stats = ServerStats::default();
+        stats.record(Some("a"), 100);
+        stats.record(None, 200);
+
+        let json = stats.to_json();
+        assert_eq!(json["a"]["responses"], 1);
+        assert_eq!(json["a"]["latencies_us"]["99"], 100);
+        assert_eq!(json[UNKNOWN_SERVER]["responses"], 1);
+    }
+}
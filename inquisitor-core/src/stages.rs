@@ -0,0 +1,207 @@
+use crate::error::InquisitorError;
+use crate::time::{parse_duration, Microseconds};
+use hdrhistogram::Histogram;
+use std::time::Duration;
+
+/// Phase of a staged load profile
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Stage {
+    pub duration: Duration,
+    /// Number of connections sending requests during the stage
+    pub connections: usize,
+    /// Maximum number of requests per second sent by all connections
+    /// together during the stage
+    pub rate: Option<f64>,
+}
+
+impl std::str::FromStr for Stage {
+    type Err = InquisitorError;
+
+    /// Parse a stage in the `DURATION:CONNECTIONS[:RATE]` format, e.g. `2m:10`
+    /// or `30s:50:200`
+    fn from_str(stage: &str) -> Result<Self, Self::Err> {
+        let error = || InquisitorError::InvalidStage(stage.to_string());
+        let mut parts = stage.split(':');
+
+        let duration = parse_duration(parts.next().ok_or_else(error)?).map_err(|_| error())?;
+        let connections = parts
+            .next()
+            .and_then(|c| c.parse().ok())
+            .filter(|c| *c > 0)
+            .ok_or_else(error)?;
+        let rate = match parts.next() {
+            Some(rate) => Some(
+                rate.parse::<f64>()
+                    .ok()
+                    .filter(|r| *r > 0.0 && r.is_finite())
+                    .ok_or_else(error)?,
+            ),
+            None => None,
+        };
+
+        if parts.next().is_some() {
+            return Err(error());
+        }
+
+        Ok(Self {
+            duration,
+            connections,
+            rate,
+        })
+    }
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{} x {} conn.",
+            Microseconds(self.duration.as_micros() as f64),
+            self.connections
+        )?;
+        if let Some(rate) = self.rate {
+            write!(f, " at {} req./s", rate)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Index of the stage running at `elapsed` since the start of the first one,
+/// if any
+pub fn stage_at(stages: &[Stage], elapsed: Duration) -> Option<usize> {
+    let mut end = Duration::ZERO;
+
+    stages.iter().position(|stage| {
+        end += stage.duration;
+        elapsed < end
+    })
+}
+
+/// Time from the start of the first stage to the start of the given one
+pub fn stage_start(stages: &[Stage], index: usize) -> Duration {
+    stages.iter().take(index).map(|stage| stage.duration).sum()
+}
+
+/// Total duration of all stages
+pub fn total_duration(stages: &[Stage]) -> Duration {
+    stage_start(stages, stages.len())
+}
+
+/// Statistics of the requests started during one stage
+#[derive(Debug)]
+pub struct StageStats {
+    /// Response times in microseconds
+    pub times: Histogram<u64>,
+    pub errors: usize,
+}
+
+impl Default for StageStats {
+    fn default() -> Self {
+        Self {
+            times: crate::new_histogram(),
+            errors: 0,
+        }
+    }
+}
+
+impl StageStats {
+    /// Add the statistics in `other` to these
+    pub fn merge(&mut self, other: StageStats) {
+        self.times.add(other.times).expect("time out of bounds");
+        self.errors += other.errors;
+    }
+}
+
+/// Results of a stage of the load profile
+#[derive(Debug)]
+pub struct StageResults {
+    pub stage: Stage,
+    pub stats: StageStats,
+    /// Time the stage actually ran, shorter than its duration if the test
+    /// ended early
+    pub elapsed: Duration,
+}
+
+impl StageResults {
+    /// Requests per second during the stage
+    pub fn throughput(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            0.0
+        } else {
+            self.stats.times.len() as f64 / self.elapsed.as_secs_f64()
+        }
+    }
+
+    /// Stage results as a JSON object, with times in microseconds
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "duration_us": self.stage.duration.as_micros() as u64,
+            "connections": self.stage.connections,
+            "rate": self.stage.rate,
+            "requests": self.stats.times.len(),
+            "errors": self.stats.errors,
+            "throughput": self.throughput(),
+            "latencies_us": {
+                "50": self.stats.times.value_at_quantile(0.5),
+                "99": self.stats.times.value_at_quantile(0.99),
+            },
+        })
+    }
+}
+
+impl std::fmt::Display for StageResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}\t{} req./s\terrors {}/{}\tp50 {}\tp99 {}",
+            self.stage,
+            self.throughput() as usize,
+            self.stats.errors,
+            self.stats.times.len(),
+            Microseconds(self.stats.times.value_at_quantile(0.5) as f64),
+            Microseconds(self.stats.times.value_at_quantile(0.99) as f64),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stages() {
+        assert_eq!(
+            "2m:10".parse::<Stage>().unwrap(),
+            Stage {
+                duration: Duration::from_secs(120),
+                connections: 10,
+                rate: None
+            }
+        );
+        assert_eq!("30s:50:200".parse::<Stage>().unwrap().rate, Some(200.0));
+        assert!("2m".parse::<Stage>().is_err());
+        assert!("2m:0".parse::<Stage>().is_err());
+        assert!("2m:ten".parse::<Stage>().is_err());
+        assert!("2m:10:0".parse::<Stage>().is_err());
+        assert!("2m:10:5:1".parse::<Stage>().is_err());
+        assert!("soon:10".parse::<Stage>().is_err());
+    }
+
+    #[test]
+    fn finds_current_stage() {
+        let stages: Vec<Stage> = ["1s:1", "2s:5", "1s:10"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let at = |ms| stage_at(&stages, Duration::from_millis(ms));
+
+        assert_eq!(at(0), Some(0));
+        assert_eq!(at(999), Some(0));
+        assert_eq!(at(1000), Some(1));
+        assert_eq!(at(3500), Some(2));
+        assert_eq!(at(4000), None);
+        assert_eq!(stage_start(&stages, 2), Duration::from_secs(3));
+        assert_eq!(total_duration(&stages), Duration::from_secs(4));
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc::Receiver;
+
+/// Interval at which the pending metrics are sent
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Maximum size of a datagram, small enough not to be fragmented on common
+/// networks
+const MAX_DATAGRAM: usize = 1432;
+
+/// Number of samples waiting to be reported from which new ones are dropped
+pub const SAMPLE_BUFFER: usize = 65_536;
+
+/// Outcome of a request, reported as StatsD metrics
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sample {
+    /// Response time, in microseconds
+    pub latency: u64,
+    pub passed: bool,
+    /// Status code of the response, if there was one
+    pub status: Option<u16>,
+}
+
+/// Metrics waiting to be sent: a timing per request, and the number of
+/// requests for each result and status class
+#[derive(Debug, Default)]
+struct Batch {
+    timings: Vec<String>,
+    counts: BTreeMap<(&'static str, String), usize>,
+}
+
+impl Batch {
+    fn add(&mut self, sample: Sample) {
+        self.timings.push(format!(
+            "inquisitor.request_time:{}|ms",
+            sample.latency as f64 / 1000.0
+        ));
+
+        let result = if sample.passed { "pass" } else { "error" };
+        let class = match sample.status {
+            Some(status) => format!("{}xx", status / 100),
+            None => "io-error".to_string(),
+        };
+        *self.counts.entry((result, class)).or_default() += 1;
+    }
+
+    /// Take the pending metrics, packed into as few datagrams as possible
+    fn take(&mut self) -> Vec<String> {
+        let counts =
+            std::mem::take(&mut self.counts)
+                .into_iter()
+                .map(|((result, class), count)| {
+                    format!(
+                        "inquisitor.requests:{}|c|#result:{},status_class:{}",
+                        count, result, class
+                    )
+                });
+        let lines: Vec<_> = self.timings.drain(..).chain(counts).collect();
+
+        pack(&lines)
+    }
+}
+
+/// Join metrics with line breaks into datagrams of at most `MAX_DATAGRAM`
+/// bytes (unless a single metric is longer)
+fn pack(lines: &[String]) -> Vec<String> {
+    let mut datagrams = Vec::new();
+    let mut datagram = String::new();
+
+    for line in lines {
+        if !datagram.is_empty() && datagram.len() + 1 + line.len() > MAX_DATAGRAM {
+            datagrams.push(std::mem::take(&mut datagram));
+        }
+        if !datagram.is_empty() {
+            datagram.push('\n');
+        }
+        datagram.push_str(line);
+    }
+    if !datagram.is_empty() {
+        datagrams.push(datagram);
+    }
+
+    datagrams
+}
+
+/// Socket sending to the StatsD server at the given address (`host:port`)
+pub async fn connect(addr: &str) -> std::io::Result<UdpSocket> {
+    let server = tokio::net::lookup_host(addr)
+        .await?
+        .next()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no address found"))?;
+    let local: SocketAddr = match server {
+        SocketAddr::V4(_) => ([0, 0, 0, 0], 0).into(),
+        SocketAddr::V6(_) => ([0; 8], 0).into(),
+    };
+
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(server).await?;
+    Ok(socket)
+}
+
+/// Send the samples received as batches of metrics until all the senders
+/// are gone, giving the socket back for the final metrics
+pub async fn report(socket: UdpSocket, mut samples: Receiver<Sample>) -> UdpSocket {
+    let mut batch = Batch::default();
+    let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+
+    loop {
+        tokio::select! {
+            sample = samples.recv() => match sample {
+                Some(sample) => batch.add(sample),
+                None => break,
+            },
+            _ = flush.tick() => send(&socket, batch.take()).await,
+        }
+    }
+    send(&socket, batch.take()).await;
+
+    socket
+}
+
+/// Send a gauge with the given value
+pub async fn send_gauge(socket: &UdpSocket, name: &str, value: f64) {
+    send(socket, vec![format!("{}:{}|g", name, value)]).await;
+}
+
+async fn send(socket: &UdpSocket, datagrams: Vec<String>) {
+    for datagram in datagrams {
+        // metrics are not worth failing (or slowing down) the test for
+        let _ = socket.send(datagram.as_bytes()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_metrics() {
+        let mut batch = Batch::default();
+        for (latency, passed, status) in [
+            (1500, true, Some(200)),
+            (2000, true, Some(204)),
+            (250, false, Some(503)),
+            (30_000, false, None),
+        ] {
+            batch.add(Sample {
+                latency,
+                passed,
+                status,
+            });
+        }
+
+        assert_eq!(
+            batch.take(),
+            vec![[
+                "inquisitor.request_time:1.5|ms",
+                "inquisitor.request_time:2|ms",
+                "inquisitor.request_time:0.25|ms",
+                "inquisitor.request_time:30|ms",
+                "inquisitor.requests:1|c|#result:error,status_class:5xx",
+                "inquisitor.requests:1|c|#result:error,status_class:io-error",
+                "inquisitor.requests:2|c|#result:pass,status_class:2xx",
+            ]
+            .join("\n")]
+        );
+        assert!(batch.take().is_empty());
+    }
+
+    #[test]
+    fn packs_datagrams() {
+        let lines: Vec<_> = (0..200).map(|i| format!("metric:{}|c", i)).collect();
+        let datagrams = pack(&lines);
+
+        assert!(datagrams.len() > 1);
+        assert!(datagrams.iter().all(|d| d.len() <= MAX_DATAGRAM));
+        assert_eq!(datagrams.join("\n"), lines.join("\n"));
+    }
+}
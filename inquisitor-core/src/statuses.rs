@@ -0,0 +1,176 @@
+use crate::error::InquisitorError;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Number of responses received with each HTTP status code, and of requests
+/// that got no response at all
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StatusCounts {
+    statuses: BTreeMap<u16, usize>,
+    /// Requests that failed before a response was received (e.g. connection
+    /// errors and timeouts)
+    transport_errors: usize,
+}
+
+impl StatusCounts {
+    /// Count one response with the given status code
+    pub fn record(&mut self, status: u16) {
+        *self.statuses.entry(status).or_default() += 1;
+    }
+
+    /// Count one request that got no response
+    pub fn record_transport_error(&mut self) {
+        self.transport_errors += 1;
+    }
+
+    /// Add the responses counted in `other` to these
+    pub fn merge(&mut self, other: StatusCounts) {
+        for (status, count) in other.statuses {
+            *self.statuses.entry(status).or_default() += count;
+        }
+        self.transport_errors += other.transport_errors;
+    }
+
+    /// Number of responses with the given status code
+    pub fn get(&self, status: u16) -> usize {
+        self.statuses.get(&status).copied().unwrap_or_default()
+    }
+
+    /// Number of requests that got no response
+    pub fn transport_errors(&self) -> usize {
+        self.transport_errors
+    }
+
+    /// Counts per status code, and of requests that got no response under
+    /// `io-error`, as a JSON object
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut counts: serde_json::Map<_, _> = self
+            .statuses
+            .iter()
+            .map(|(status, count)| (status.to_string(), (*count).into()))
+            .collect();
+        counts.insert("io-error".to_string(), self.transport_errors.into());
+
+        counts.into()
+    }
+}
+
+impl std::fmt::Display for StatusCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "status codes: ")?;
+        for (status, count) in &self.statuses {
+            write!(f, "{}: {}, ", status, count)?;
+        }
+        write!(f, "io-error: {}", self.transport_errors)
+    }
+}
+
+/// Status codes of the responses that pass, as a list of single codes and
+/// ranges (any 2xx by default)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusSet(Vec<RangeInclusive<u16>>);
+
+impl Default for StatusSet {
+    fn default() -> Self {
+        Self(vec![200..=299])
+    }
+}
+
+impl StatusSet {
+    /// Whether the status code is in the set
+    pub fn contains(&self, status: u16) -> bool {
+        self.0.iter().any(|range| range.contains(&status))
+    }
+}
+
+impl std::str::FromStr for StatusSet {
+    type Err = InquisitorError;
+
+    /// Parse a comma-separated list of codes (`201`) and ranges (`301-302`)
+    fn from_str(statuses: &str) -> Result<Self, Self::Err> {
+        let error = || InquisitorError::InvalidStatusSet(statuses.to_string());
+        let status = |code: &str| match code.trim().parse() {
+            Ok(code @ 100..=599) => Ok(code),
+            _ => Err(error()),
+        };
+
+        statuses
+            .split(',')
+            .map(|item| {
+                let (min, max) = item.split_once('-').unwrap_or((item, item));
+                let (min, max) = (status(min)?, status(max)?);
+                if min > max {
+                    return Err(error());
+                }
+                Ok(min..=max)
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl std::fmt::Display for StatusSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        for (i, range) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match (range.start(), range.end()) {
+                (min, max) if min == max => write!(f, "{}", min)?,
+                (min, max) => write!(f, "{}-{}", min, max)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_status_sets() {
+        let single: StatusSet = "204".parse().unwrap();
+        assert!(single.contains(204));
+        assert!(!single.contains(200));
+
+        let range: StatusSet = "301-302".parse().unwrap();
+        assert!(range.contains(301) && range.contains(302));
+        assert!(!range.contains(300) && !range.contains(303));
+
+        let mixed: StatusSet = "200, 201,301-302,404".parse().unwrap();
+        assert!([200, 201, 301, 302, 404].iter().all(|s| mixed.contains(*s)));
+        assert!(!mixed.contains(202));
+        assert_eq!(mixed.to_string(), "200,201,301-302,404");
+
+        assert!(StatusSet::default().contains(250));
+        assert!(!StatusSet::default().contains(301));
+
+        for invalid in ["", "20", "200-", "302-301", "abc", "200,,201", "600"] {
+            assert!(invalid.parse::<StatusSet>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn counts_statuses() {
+        let mut statuses = StatusCounts::default();
+        statuses.record(502);
+        statuses.record(200);
+
+        let mut other = StatusCounts::default();
+        other.record(200);
+        other.record_transport_error();
+        statuses.merge(other);
+
+        assert_eq!(statuses.get(200), 2);
+        assert_eq!(statuses.get(404), 0);
+        assert_eq!(
+            statuses.to_string(),
+            "status codes: 200: 2, 502: 1, io-error: 1"
+        );
+        assert_eq!(
+            statuses.to_json(),
+            serde_json::json!({ "200": 2, "502": 1, "io-error": 1 })
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use crate::error::InquisitorError;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Part of a template
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    /// `{seq}`: number of the request, starting at 1
+    Sequence,
+    /// `{rand:MIN-MAX}`: random integer between the bounds (included)
+    Random(u64, u64),
+    /// `{uuid}`: random (version 4) UUID
+    Uuid,
+    /// `{NAME}`: value of the column at the given index in the current row
+    /// of the data file
+    Column(usize),
+}
+
+/// Text (e.g. a URL) with placeholders replaced by a different value for
+/// each request
+///
+/// Braces that do not enclose a known placeholder are kept as they are, so
+/// text without placeholders is sent unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template(Vec<Part>);
+
+impl std::str::FromStr for Template {
+    type Err = InquisitorError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text, &[])
+    }
+}
+
+impl Template {
+    /// Parse a template whose placeholders can also refer to the given data
+    /// columns (the built-in placeholders take precedence)
+    pub fn parse(text: &str, columns: &[String]) -> Result<Self, InquisitorError> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            let placeholder = &rest[start..start + end + 1];
+
+            let part = match &placeholder[1..placeholder.len() - 1] {
+                "seq" => Some(Part::Sequence),
+                "uuid" => Some(Part::Uuid),
+                name => match name.strip_prefix("rand:") {
+                    Some(range) => Some(parse_range(range).ok_or_else(|| {
+                        InquisitorError::InvalidPlaceholder(
+                            placeholder.to_string(),
+                            "expected {rand:MIN-MAX}, e.g. {rand:1-1000}".to_string(),
+                        )
+                    })?),
+                    None => columns.iter().position(|c| c == name).map(Part::Column),
+                },
+            };
+
+            match part {
+                Some(part) => {
+                    literal.push_str(&rest[..start]);
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(part);
+                    rest = &rest[start + end + 1..];
+                }
+                // the brace is kept, and a placeholder may start after it
+                // (e.g. in `{"id": {id}}`)
+                None => {
+                    literal.push_str(&rest[..=start]);
+                    rest = &rest[start + 1..];
+                }
+            }
+        }
+
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Ok(Self(parts))
+    }
+
+    /// Whether the text has no placeholders
+    pub fn is_plain(&self) -> bool {
+        self.0.iter().all(|part| matches!(part, Part::Literal(_)))
+    }
+
+    /// Whether the text has placeholders replaced by random values
+    pub fn is_random(&self) -> bool {
+        self.0
+            .iter()
+            .any(|part| matches!(part, Part::Random(..) | Part::Uuid))
+    }
+
+    /// Text of the request with the given sequence number and data row,
+    /// drawing the random values from `rng`
+    pub fn render(&self, seq: usize, row: &[String], rng: &mut impl Rng) -> String {
+        let mut text = String::new();
+
+        for part in &self.0 {
+            match part {
+                Part::Literal(literal) => text.push_str(literal),
+                Part::Sequence => text.push_str(&seq.to_string()),
+                Part::Random(min, max) => text.push_str(&rng.gen_range(*min..=*max).to_string()),
+                Part::Uuid => text.push_str(&uuid(rng)),
+                Part::Column(i) => text.push_str(&row[*i]),
+            }
+        }
+
+        text
+    }
+}
+
+/// Parse the `MIN-MAX` range of a `{rand}` placeholder
+fn parse_range(range: &str) -> Option<Part> {
+    let (min, max) = range.split_once('-')?;
+    let (min, max) = (min.trim().parse().ok()?, max.trim().parse().ok()?);
+
+    (min <= max).then_some(Part::Random(min, max))
+}
+
+/// Parts of a request with placeholders, replaced with the same values in
+/// all of them
+pub struct RequestTemplate {
+    pub url: Template,
+    pub headers: HashMap<String, Template>,
+    /// Request body, if it is text with placeholders
+    pub body: Option<Template>,
+}
+
+/// Request with its placeholders replaced
+pub struct RenderedRequest {
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    pub body: Option<String>,
+}
+
+impl RequestTemplate {
+    /// Parse the placeholders of the URL, headers and body of a request (the
+    /// latter only if it is valid UTF-8)
+    pub fn parse(
+        url: &str,
+        headers: &HashMap<String, String>,
+        body: Option<&[u8]>,
+        columns: &[String],
+    ) -> Result<Self, InquisitorError> {
+        let headers = headers
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), Template::parse(v, columns)?)))
+            .collect::<Result<_, InquisitorError>>()?;
+        let body = match body.and_then(|body| std::str::from_utf8(body).ok()) {
+            Some(body) => Some(Template::parse(body, columns)?).filter(|body| !body.is_plain()),
+            None => None,
+        };
+
+        Ok(Self {
+            url: Template::parse(url, columns)?,
+            headers,
+            body,
+        })
+    }
+
+    /// Whether no part of the request has placeholders
+    pub fn is_plain(&self) -> bool {
+        self.url.is_plain() && self.headers.values().all(Template::is_plain) && self.body.is_none()
+    }
+
+    /// Whether a part of the request has placeholders replaced by random
+    /// values
+    pub fn is_random(&self) -> bool {
+        self.url.is_random()
+            || self.headers.values().any(Template::is_random)
+            || self.body.as_ref().is_some_and(Template::is_random)
+    }
+
+    /// Request with the given sequence number and data row, drawing the
+    /// random values from `rng`
+    pub fn render(&self, seq: usize, row: &[String], rng: &mut impl Rng) -> RenderedRequest {
+        RenderedRequest {
+            url: self.url.render(seq, row, rng),
+            headers: self
+                .headers
+                .iter()
+                .map(|(k, v)| (k.clone(), v.render(seq, row, rng)))
+                .collect(),
+            body: self.body.as_ref().map(|body| body.render(seq, row, rng)),
+        }
+    }
+}
+
+/// Random (version 4) UUID in its hyphenated form
+fn uuid(rng: &mut impl Rng) -> String {
+    let mut bytes: [u8; 16] = rng.gen();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn render(url: &str, seq: usize) -> String {
+        let template: Template = url.parse().unwrap();
+        template.render(seq, &[], &mut StdRng::seed_from_u64(1))
+    }
+
+    #[test]
+    fn keeps_plain_urls() {
+        for url in [
+            "http://localhost:8080/test?a=1",
+            "http://localhost/{id}/{rand}",
+            "http://localhost/{seq",
+        ] {
+            let template: Template = url.parse().unwrap();
+            assert!(template.is_plain());
+            assert_eq!(render(url, 1), url);
+        }
+    }
+
+    #[test]
+    fn replaces_placeholders() {
+        assert_eq!(render("http://a/item/{seq}", 42), "http://a/item/42");
+        assert_eq!(
+            render("http://a/{seq}/{seq}?x={seq}", 7),
+            "http://a/7/7?x=7"
+        );
+
+        let template: Template = "http://a/user/{rand:1-3}".parse().unwrap();
+        assert!(template.is_random());
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            let id: u64 = template.render(1, &[], &mut rng)["http://a/user/".len()..]
+                .parse()
+                .unwrap();
+            assert!((1..=3).contains(&id));
+        }
+        assert_eq!(render("http://a/{rand:5-5}", 1), "http://a/5");
+
+        let template: Template = "{uuid}".parse().unwrap();
+        let uuid = template.render(1, &[], &mut rng);
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(uuid.matches('-').count(), 4);
+        assert_eq!(uuid.as_bytes()[14], b'4');
+        assert_ne!(uuid, template.render(1, &[], &mut rng));
+    }
+
+    #[test]
+    fn replaces_columns_in_all_parts() {
+        let columns = ["id".to_string(), "seq".to_string()];
+        let headers = HashMap::from([("Authorization".to_string(), "Bearer {id}".to_string())]);
+        let template = RequestTemplate::parse(
+            "http://a/{id}/{seq}/{other}",
+            &headers,
+            Some(b"{\"id\": {id}}"),
+            &columns,
+        )
+        .unwrap();
+        assert!(!template.is_plain());
+        assert!(!template.is_random());
+
+        let row = ["7".to_string(), "column".to_string()];
+        let request = template.render(3, &row, &mut StdRng::seed_from_u64(1));
+        assert_eq!(request.url, "http://a/7/3/{other}");
+        assert_eq!(request.headers["Authorization"], "Bearer 7");
+        assert_eq!(request.body.as_deref(), Some("{\"id\": 7}"));
+
+        let plain = RequestTemplate::parse("http://a/", &headers, Some(b"{id}"), &[]).unwrap();
+        assert!(plain.is_plain());
+        let binary = RequestTemplate::parse("http://a/", &HashMap::new(), Some(b"\xff{seq}"), &[]);
+        assert!(binary.unwrap().is_plain());
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        assert!("http://a/{rand:5-1}".parse::<Template>().is_err());
+        assert!("http://a/{rand:1}".parse::<Template>().is_err());
+        assert!("http://a/{rand:a-b}".parse::<Template>().is_err());
+    }
+}
@@ -0,0 +1,295 @@
+use crate::error::InquisitorError;
+use crate::repeats::RepeatedResults;
+use crate::results::{budget_consumed, quantile, TestResults};
+use crate::time::{parse_duration, Microseconds};
+
+/// Measurement of a test compared to a threshold
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Metric {
+    /// `pN`: response time at the given percentile, e.g. `p99` or `p99.9`
+    Percentile(f64),
+    /// `mean`: mean response time
+    Mean,
+    /// `max`: maximum response time
+    Max,
+    /// `error_rate`: percentage of the requests that failed
+    ErrorRate,
+    /// `errors`: number of requests that failed
+    Errors,
+    /// `rps`: requests per second, while requests were being sent
+    Rps,
+    /// `budget_burn`: percentage of the error budget of the SLO consumed
+    BudgetBurn,
+}
+
+impl Metric {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "mean" => Some(Self::Mean),
+            "max" => Some(Self::Max),
+            "error_rate" => Some(Self::ErrorRate),
+            "errors" => Some(Self::Errors),
+            "rps" => Some(Self::Rps),
+            "budget_burn" => Some(Self::BudgetBurn),
+            _ => match name.strip_prefix('p')?.parse() {
+                Ok(p) if p > 0.0 && p < 100.0 => Some(Self::Percentile(p)),
+                _ => None,
+            },
+        }
+    }
+
+    fn is_time(self) -> bool {
+        matches!(self, Self::Percentile(_) | Self::Mean | Self::Max)
+    }
+
+    /// Value of the metric for a run, with times in microseconds
+    pub fn measure(self, run: &TestResults) -> f64 {
+        match self {
+            Self::Percentile(p) => run.percentile(quantile(p)) as f64,
+            Self::Mean => run.times.mean(),
+            Self::Max => run.times.max() as f64,
+            Self::ErrorRate => 100.0 * run.error_rate(),
+            Self::Errors => run.errors as f64,
+            Self::Rps => run.throughput(),
+            // not measured without an SLO
+            Self::BudgetBurn => match run.slo_availability {
+                Some(slo) => budget_consumed(run.error_rate(), slo),
+                None => f64::NAN,
+            },
+        }
+    }
+
+    /// Value of the metric over the runs, picked by `gate_on` where the
+    /// worst run is the one with the highest value if `higher_fails`, and
+    /// the lowest otherwise
+    pub fn select(self, results: &RepeatedResults, gate_on: GateOn, higher_fails: bool) -> f64 {
+        let spread = results.spread(|run| self.measure(run));
+        match (gate_on, higher_fails) {
+            (GateOn::Median, _) => spread.median,
+            (GateOn::Worst, true) | (GateOn::Best, false) => spread.max,
+            (GateOn::Worst, false) | (GateOn::Best, true) => spread.min,
+        }
+    }
+
+    /// Value of the metric in its unit
+    fn format(self, value: f64) -> String {
+        match self {
+            Self::ErrorRate => format!("{:.2}%", value),
+            Self::BudgetBurn => format!("{:.1}%", value),
+            Self::Errors => format!("{}", value),
+            Self::Rps => format!("{:.1}", value),
+            _ => Microseconds(value).to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::Percentile(p) => write!(f, "p{}", p),
+            Self::Mean => write!(f, "mean"),
+            Self::Max => write!(f, "max"),
+            Self::ErrorRate => write!(f, "error_rate"),
+            Self::Errors => write!(f, "errors"),
+            Self::Rps => write!(f, "rps"),
+            Self::BudgetBurn => write!(f, "budget_burn"),
+        }
+    }
+}
+
+/// Which run of a repeated test the thresholds are checked against
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum GateOn {
+    /// The run closest to violating each threshold
+    Worst,
+    /// The median of the runs, for each metric
+    #[default]
+    Median,
+    /// The run furthest from violating each threshold
+    Best,
+}
+
+/// How a measurement is compared to the limit of a threshold
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl Comparison {
+    fn holds(self, value: f64, limit: f64) -> bool {
+        match self {
+            Self::Greater => value > limit,
+            Self::GreaterOrEqual => value >= limit,
+            Self::Less => value < limit,
+            Self::LessOrEqual => value <= limit,
+        }
+    }
+}
+
+/// Condition under which a test fails, e.g. `p99>250ms`, `error_rate>1%` or
+/// `rps<500`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Threshold {
+    pub metric: Metric,
+    pub comparison: Comparison,
+    /// Limit of the metric, with times in microseconds and the error rate
+    /// and budget burn in percent
+    pub limit: f64,
+    text: String,
+}
+
+impl std::str::FromStr for Threshold {
+    type Err = InquisitorError;
+
+    fn from_str(threshold: &str) -> Result<Self, Self::Err> {
+        let error = |reason: &str| {
+            InquisitorError::InvalidThreshold(threshold.to_string(), reason.to_string())
+        };
+
+        let at = threshold
+            .find(['<', '>'])
+            .ok_or_else(|| error("expected METRIC>VALUE or METRIC<VALUE, e.g. p99>250ms"))?;
+        let (name, rest) = threshold.split_at(at);
+        let (comparison, value) = match rest.split_at(1) {
+            (">", value) => match value.strip_prefix('=') {
+                Some(value) => (Comparison::GreaterOrEqual, value),
+                None => (Comparison::Greater, value),
+            },
+            (_, value) => match value.strip_prefix('=') {
+                Some(value) => (Comparison::LessOrEqual, value),
+                None => (Comparison::Less, value),
+            },
+        };
+
+        let metric = Metric::parse(name.trim()).ok_or_else(|| {
+            error(
+                "unknown metric, expected pN (e.g. p99), mean, max, error_rate, errors, rps or \
+                 budget_burn",
+            )
+        })?;
+        let value = value.trim();
+        let limit = if metric.is_time() {
+            parse_time(value).ok_or_else(|| error("expected a time, e.g. 250ms or 1.5s"))?
+        } else {
+            let number = match metric {
+                Metric::ErrorRate | Metric::BudgetBurn => value.strip_suffix('%').unwrap_or(value),
+                _ => value,
+            };
+            number
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .ok_or_else(|| error("expected a number"))?
+        };
+
+        Ok(Self {
+            metric,
+            comparison,
+            limit,
+            text: threshold.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for Threshold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl Threshold {
+    /// Description of how the results violate the threshold, if they do,
+    /// with the run picked by `gate_on`
+    pub fn check(&self, results: &RepeatedResults, gate_on: GateOn) -> Option<String> {
+        let higher_fails = matches!(
+            self.comparison,
+            Comparison::Greater | Comparison::GreaterOrEqual
+        );
+        let value = self.metric.select(results, gate_on, higher_fails);
+
+        self.comparison
+            .holds(value, self.limit)
+            .then(|| format!("{} was {}", self.metric, self.metric.format(value)))
+    }
+}
+
+/// Parse a time in microseconds, in microseconds (`us`) or any unit of
+/// `parse_duration`
+fn parse_time(time: &str) -> Option<f64> {
+    let number = |n: &str| n.parse::<f64>().ok().filter(|n| n.is_finite());
+
+    match time.strip_suffix("us") {
+        Some(us) => number(us),
+        None => parse_duration(time).ok().map(|d| d.as_micros() as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(threshold: &str) -> Threshold {
+        threshold.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_metrics() {
+        assert_eq!(parse("p99>1s").metric, Metric::Percentile(99.0));
+        assert_eq!(parse("p99.9>1s").metric, Metric::Percentile(99.9));
+        assert_eq!(parse("mean>1s").metric, Metric::Mean);
+        assert_eq!(parse("max>1s").metric, Metric::Max);
+        assert_eq!(parse("error_rate>1").metric, Metric::ErrorRate);
+        assert_eq!(parse("errors>1").metric, Metric::Errors);
+        assert_eq!(parse("rps<1").metric, Metric::Rps);
+        assert_eq!(parse("budget_burn>100%").metric, Metric::BudgetBurn);
+
+        for invalid in ["p100>1s", "p0>1s", "px>1s", "latency>1s", ">1s"] {
+            assert!(invalid.parse::<Threshold>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn parses_comparisons() {
+        assert_eq!(parse("rps>1").comparison, Comparison::Greater);
+        assert_eq!(parse("rps>=1").comparison, Comparison::GreaterOrEqual);
+        assert_eq!(parse("rps<1").comparison, Comparison::Less);
+        assert_eq!(parse("rps <= 1").comparison, Comparison::LessOrEqual);
+        assert!("rps=1".parse::<Threshold>().is_err());
+        assert!("rps".parse::<Threshold>().is_err());
+
+        assert!(Comparison::Greater.holds(2.0, 1.0));
+        assert!(!Comparison::Greater.holds(1.0, 1.0));
+        assert!(Comparison::GreaterOrEqual.holds(1.0, 1.0));
+        assert!(Comparison::Less.holds(0.5, 1.0));
+        assert!(!Comparison::Less.holds(1.0, 1.0));
+        assert!(Comparison::LessOrEqual.holds(1.0, 1.0));
+    }
+
+    #[test]
+    fn parses_units() {
+        assert_eq!(parse("p99>250ms").limit, 250_000.0);
+        assert_eq!(parse("p99>500us").limit, 500.0);
+        assert_eq!(parse("p99>1.5s").limit, 1_500_000.0);
+        assert_eq!(parse("max>2m").limit, 120_000_000.0);
+        assert_eq!(parse("max>1d").limit, 86_400_000_000.0);
+        assert_eq!(parse("error_rate>1%").limit, 1.0);
+        assert_eq!(parse("error_rate>0.5").limit, 0.5);
+        assert_eq!(parse("rps<500").limit, 500.0);
+        assert_eq!(parse("budget_burn>100%").limit, 100.0);
+
+        for invalid in [
+            "p99>250",
+            "p99>fast",
+            "p99>1x",
+            "p99>1.5.1s",
+            "p99>1é",
+            "rps<5%",
+            "errors>",
+        ] {
+            assert!(invalid.parse::<Threshold>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -19,32 +19,81 @@ impl std::fmt::Display for Microseconds {
     }
 }
 
-/// Parse a duration like 10s, 25m, 3.5h into a duration. Decimal numbers are
-/// allowed, and the allowed time units are: seconds (s), minutes (m) and
-/// hours (h).
+/// Parse a duration like 500ms, 10s, 25m, 3.5h or 2d into a duration. Decimal
+/// numbers are allowed, and the allowed time units are: milliseconds (ms),
+/// seconds (s), minutes (m), hours (h) and days (d). Several of them can be
+/// combined, e.g. 1h30m15s, and are added up.
 pub fn parse_duration(duration: &str) -> Result<Duration, InquisitorError> {
-    let re = regex::Regex::new(r"(\d\d*(?:\.\d\d*)??)([smh])").expect("Bug: wrong regex");
-    let cap = re
-        .captures(duration)
-        .ok_or(InquisitorError::DurationParseError)?;
+    // `ms` comes first, not to be taken for minutes
+    const SEGMENT: &str = r"(\d+(?:\.\d+)?)(ms|[smhd])";
+    let whole = regex::Regex::new(&format!("^(?:{})+$", SEGMENT)).expect("Bug: wrong regex");
+    let error = || InquisitorError::DurationParseError(duration.to_string());
+    if !whole.is_match(duration) {
+        return Err(error());
+    }
 
-    let base = cap[1]
-        .parse::<f64>()
-        .map_err(|_| InquisitorError::DurationParseError)?;
-    let mul: f64 = match &cap[2] {
-        "s" => 1_000_000.0,
-        "m" => 60.0 * 1_000_000.0,
-        "h" => 60.0 * 60.0 * 1_000_000.0,
-        _ => unreachable!(),
-    };
+    let segment = regex::Regex::new(SEGMENT).expect("Bug: wrong regex");
+    let mut micros = 0.0;
+    for cap in segment.captures_iter(duration) {
+        let base = cap[1].parse::<f64>().map_err(|_| error())?;
+        let mul: f64 = match &cap[2] {
+            "ms" => 1_000.0,
+            "s" => 1_000_000.0,
+            "m" => 60.0 * 1_000_000.0,
+            "h" => 60.0 * 60.0 * 1_000_000.0,
+            "d" => 24.0 * 60.0 * 60.0 * 1_000_000.0,
+            _ => unreachable!(),
+        };
+        micros += base * mul;
+    }
 
-    Ok(Duration::from_micros((base * mul) as u64))
+    Ok(Duration::from_micros(micros as u64))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("10s").unwrap(), Duration::from_secs(10));
+        assert_eq!(parse_duration("1.5m").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("20h").unwrap(), Duration::from_secs(72_000));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("0.5ms").unwrap(), Duration::from_micros(500));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::from_secs(172_800));
+        assert!(parse_duration("10").is_err());
+        assert!(parse_duration("s").is_err());
+    }
+
+    #[test]
+    fn parses_compound_durations() {
+        assert_eq!(
+            parse_duration("1h30m15s").unwrap(),
+            Duration::from_secs(5415)
+        );
+        assert_eq!(parse_duration("1m30s").unwrap(), Duration::from_secs(90));
+        assert_eq!(
+            parse_duration("1s500ms").unwrap(),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(
+            parse_duration("1d12h").unwrap(),
+            Duration::from_secs(129_600)
+        );
+
+        assert_eq!(
+            parse_duration("1h30x").unwrap_err().to_string(),
+            "invalid duration '1h30x': expected a number followed by ms, s, m, h or d, e.g. \
+             10s or 1m30s"
+        );
+        for malformed in [
+            "", "1h30x", "1h30", "x1s", "1s ", "1h 30m", "1.s", "1.5.1s", "-1s",
+        ] {
+            assert!(parse_duration(malformed).is_err(), "{}", malformed);
+        }
+    }
+
     #[test]
     fn displays_time_correctly() {
         assert_eq!(Microseconds(0.0).to_string(), "0 us");
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+/// Interval between two samples (of the p99, or of the error budget
+/// consumption) used for the trend analysis
+pub const TREND_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Relative change per minute (in percent) below which the latency (or the
+/// budget consumption) is considered to be stable
+pub const STABLE_THRESHOLD_PCT_PER_MIN: f64 = 5.0;
+
+/// Overall direction of the latency (or the budget consumption) during a run,
+/// where going up is degrading
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Stable,
+    Improving,
+    Degrading,
+}
+
+/// Linear trend of the p99 latency (or of the error budget consumption) over
+/// the course of a run
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Trend {
+    pub verdict: Verdict,
+    /// Relative change of the samples per minute, in percent of their mean
+    pub pct_per_min: f64,
+}
+
+impl Trend {
+    /// Fit a least-squares line to `(seconds since start, value)` samples,
+    /// e.g. of the p99 in us. At least two samples at distinct times are
+    /// needed, otherwise `None` is returned.
+    pub fn from_samples(samples: &[(f64, f64)]) -> Option<Self> {
+        if samples.len() < 2 {
+            return None;
+        }
+
+        let n = samples.len() as f64;
+        let mean_x = samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+        let (cov, var) = samples.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+            (
+                cov + (x - mean_x) * (y - mean_y),
+                var + (x - mean_x) * (x - mean_x),
+            )
+        });
+
+        if var == 0.0 {
+            return None;
+        }
+        // always zero, e.g. no error budget consumed at all
+        if mean_y == 0.0 {
+            return Some(Self {
+                verdict: Verdict::Stable,
+                pct_per_min: 0.0,
+            });
+        }
+
+        // slope is in us per second
+        let slope = cov / var;
+        let pct_per_min = slope * 60.0 / mean_y * 100.0;
+
+        let verdict = match pct_per_min {
+            x if x.abs() < STABLE_THRESHOLD_PCT_PER_MIN => Verdict::Stable,
+            x if x > 0.0 => Verdict::Degrading,
+            _ => Verdict::Improving,
+        };
+
+        Some(Self {
+            verdict,
+            pct_per_min,
+        })
+    }
+}
+
+impl Trend {
+    /// Verdict and relative change per minute as a JSON object
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "verdict": self.verdict.to_string(),
+            "pct_per_min": self.pct_per_min,
+        })
+    }
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::Stable => write!(f, "stable"),
+            Self::Improving => write!(f, "improving"),
+            Self::Degrading => write!(f, "degrading"),
+        }
+    }
+}
+
+impl std::fmt::Display for Trend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{} ({:+.1}%/min)", self.verdict, self.pct_per_min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needs_at_least_two_samples() {
+        assert_eq!(Trend::from_samples(&[]), None);
+        assert_eq!(Trend::from_samples(&[(1.0, 100.0)]), None);
+        assert_eq!(Trend::from_samples(&[(1.0, 100.0), (1.0, 200.0)]), None);
+    }
+
+    #[test]
+    fn zero_is_stable() {
+        let zero: Vec<_> = (0..5).map(|x| (x as f64, 0.0)).collect();
+        assert_eq!(Trend::from_samples(&zero).unwrap().verdict, Verdict::Stable);
+    }
+
+    #[test]
+    fn detects_trends() {
+        let flat: Vec<_> = (0..10).map(|x| (x as f64, 1000.0)).collect();
+        let up: Vec<_> = (0..10)
+            .map(|x| (x as f64, 1000.0 + 10.0 * x as f64))
+            .collect();
+        let down: Vec<_> = (0..10)
+            .map(|x| (x as f64, 2000.0 - 10.0 * x as f64))
+            .collect();
+
+        assert_eq!(Trend::from_samples(&flat).unwrap().verdict, Verdict::Stable);
+        assert_eq!(
+            Trend::from_samples(&up).unwrap().verdict,
+            Verdict::Degrading
+        );
+        assert_eq!(
+            Trend::from_samples(&down).unwrap().verdict,
+            Verdict::Improving
+        );
+    }
+
+    #[test]
+    fn displays_trend_correctly() {
+        let trend = Trend {
+            verdict: Verdict::Degrading,
+            pct_per_min: 12.345,
+        };
+        assert_eq!(trend.to_string(), "degrading (+12.3%/min)");
+    }
+}
@@ -0,0 +1,70 @@
+use hdrhistogram::Histogram;
+use std::sync::{Arc, Mutex};
+
+/// Response times recorded since they were last taken, split over the
+/// connections so that they don't wait on each other to record a request:
+/// each connection only shares its part with the task taking them
+#[derive(Clone, Default)]
+pub(crate) struct Window {
+    parts: Arc<Mutex<Vec<WindowPart>>>,
+}
+
+impl Window {
+    /// New part of the window, for a connection to record its response
+    /// times in
+    pub fn part(&self) -> WindowPart {
+        let part = WindowPart(Arc::new(Mutex::new(crate::new_histogram())));
+        self.parts.lock().unwrap().push(part.clone());
+        part
+    }
+
+    /// Response times recorded by all the connections since the last call,
+    /// in microseconds
+    pub fn take(&self) -> Histogram<u64> {
+        let mut times = crate::new_histogram();
+        for part in self.parts.lock().unwrap().iter() {
+            let mut part = part.0.lock().unwrap();
+            times.add(&*part).expect("time out of bounds");
+            part.reset();
+        }
+
+        times
+    }
+}
+
+/// Part of a `Window` a single connection records in
+#[derive(Clone)]
+pub(crate) struct WindowPart(Arc<Mutex<Histogram<u64>>>);
+
+impl WindowPart {
+    /// Record a response time, in microseconds
+    pub fn record(&self, time: u64) {
+        self.0
+            .lock()
+            .unwrap()
+            .record(time)
+            .expect("time out of bounds");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_and_resets_the_parts() {
+        let window = Window::default();
+        let (first, second) = (window.part(), window.part());
+        first.record(1_000);
+        second.record(3_000);
+        second.record(5_000);
+
+        let times = window.take();
+        assert_eq!(times.len(), 3);
+        assert_eq!(times.max(), times.highest_equivalent(5_000));
+        assert!(window.take().is_empty());
+
+        first.record(2_000);
+        assert_eq!(window.take().len(), 1);
+    }
+}